@@ -1,10 +1,30 @@
+mod announcement;
+mod calendar;
+mod courses;
+mod deadline;
+mod download;
+mod forum;
+mod info;
+mod page;
+mod quiz;
 mod video;
 
 use super::*;
 use crate::api::low_level::blackboard::BlackboardUnautherizedError;
+pub use calendar::CalendarItem;
+pub use courses::CourseTerm;
+pub use deadline::{Deadline, shanghai};
+pub use download::{
+    ClaimedPaths, DownloadTarget, OnConflict, dedup_names, numbered, sanitize_filename,
+};
+pub use forum::{CourseForumHandle, CourseThreadHandle};
+pub use quiz::{CourseQuiz, CourseQuizHandle};
 use serde::Deserialize;
 pub use video::CourseVideo;
 
+/// 教学网的地址，REST API 返回的 `nextPage` 和页面中的链接等为相对于它的路径
+pub(crate) const API_BASE: &str = "https://course.pku.edu.cn";
+
 impl Client {
     pub async fn blackboard(
         &self,
@@ -33,32 +53,127 @@ impl Client {
         })
     }
 
-    pub async fn course_attachment_download<P: AsRef<std::path::Path>>(
+    /// 获取附件链接重定向后的真实地址 (形如 `/bbcswebdav/pid-.../xid-...`)，
+    /// 教学网上的文件被替换后该地址会随之改变.
+    pub async fn course_attachment_location(&self, uri: &str) -> anyhow::Result<String> {
+        let res = self.get_by_uri(uri).await?;
+        let loc = low_level::extract_redirect_url(&res)?;
+        log::debug!("redirected to {loc}");
+        Ok(loc.to_owned())
+    }
+
+    /// 获取附件真实地址 `loc` 的 `Last-Modified` 响应头，服务器不支持时返回 `None`
+    pub async fn course_attachment_modified(&self, loc: &str) -> anyhow::Result<Option<String>> {
+        let res = self.head_by_uri(loc).await?;
+        if !res.status().is_success() {
+            log::debug!("HEAD {loc}: {}", res.status());
+            return Ok(None);
+        }
+        Ok(res
+            .headers()
+            .get(http::header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .map(ToOwned::to_owned))
+    }
+}
+
+// REST API: gradebook
+impl Client {
+    async fn gradebook_columns(&self, course_id: &str) -> anyhow::Result<Vec<GradebookColumn>> {
+        #[derive(Debug, Deserialize)]
+        struct GradebookColumns {
+            results: Vec<GradebookColumn>,
+        }
+
+        let val: GradebookColumns = self
+            .0
+            .http_client
+            .api_get(&format!(
+                "https://course.pku.edu.cn/learn/api/public/v2/courses/{}/gradebook/columns",
+                course_id
+            ))
+            .await
+            .context("fetch gradebook columns")?;
+        Ok(val.results)
+    }
+
+    /// 成绩中心的分类 (ID, 名称)
+    async fn gradebook_categories(&self, course_id: &str) -> anyhow::Result<Vec<(String, String)>> {
+        #[derive(Debug, Deserialize)]
+        struct Categories {
+            results: Vec<Category>,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Category {
+            id: String,
+            title: String,
+        }
+
+        let val: Categories = self
+            .0
+            .http_client
+            .api_get(&format!(
+                "https://course.pku.edu.cn/learn/api/public/v1/courses/{}/gradebook/categories",
+                course_id
+            ))
+            .await
+            .context("fetch gradebook categories")?;
+        Ok(val.results.into_iter().map(|c| (c.id, c.title)).collect())
+    }
+
+    /// 成绩列中当前用户的尝试记录
+    async fn gradebook_attempts(
         &self,
-        uri: &str,
-        dest: P,
-        redir: bool,
-    ) -> anyhow::Result<()> {
-        log::debug!("downloading attachment from {uri}");
-        let uri = if redir {
-            let res = self.get_by_uri(uri).await?;
-            let loc = low_level::extract_redirect_url(&res)?;
-            log::debug!("redirected to {loc}");
-            loc.to_owned()
-        } else {
-            uri.to_owned()
-        };
+        course_id: &str,
+        column_id: &str,
+    ) -> anyhow::Result<Vec<GradebookAttempt>> {
+        #[derive(Debug, Deserialize)]
+        struct Attempts {
+            results: Vec<GradebookAttempt>,
+        }
 
-        let res = self.get_by_uri(&uri).await?;
-        anyhow::ensure!(res.status().is_success(), "status not success");
+        let val: Attempts = self
+            .0
+            .http_client
+            .api_get(&format!(
+                "https://course.pku.edu.cn/learn/api/public/v2/courses/{}/gradebook/columns/{}/attempts",
+                course_id, column_id
+            ))
+            .await
+            .context("fetch attempts")?;
+        Ok(val.results)
+    }
 
-        let rbody = res.bytes().await?;
-        let r = compio::fs::write(dest, rbody).await;
-        compio::buf::buf_try!(@try r);
-        Ok(())
+    async fn gradedata(&self, course_id: &str, column_id: &str) -> anyhow::Result<Vec<GradeUser>> {
+        #[derive(Debug, Deserialize)]
+        struct GradeUsers {
+            results: Vec<GradeUser>,
+        }
+
+        let val: GradeUsers = self
+            .0
+            .http_client
+            .api_get(&format!(
+                "https://course.pku.edu.cn/learn/api/public/v2/courses/{}/gradebook/columns/{}/users",
+                course_id, column_id
+            ))
+            .await
+            .context("fetch gradebook columns")?;
+        Ok(val.results)
     }
 }
 
+/// 由名和姓拼出显示的姓名，中文姓名姓在前且不加空格
+fn person_name(given: &str, family: &str) -> String {
+    let (given, family) = (given.trim(), family.trim());
+    let name = if (given.chars().chain(family.chars())).all(|c| c.is_ascii()) {
+        format!("{given} {family}")
+    } else {
+        format!("{family}{given}")
+    };
+    name.trim().to_owned()
+}
+
 #[derive(Debug)]
 pub struct Blackboard {
     client: Client,
@@ -66,7 +181,40 @@ pub struct Blackboard {
 }
 
 impl Blackboard {
-    async fn _get_courses(&self) -> anyhow::Result<Vec<(String, String, bool)>> {
+    /// 优先通过 REST API 获取课程列表，失败时退回到解析首页的课程模块
+    async fn _get_courses(&self) -> anyhow::Result<Vec<CourseMeta>> {
+        let mut courses = match self.rest_courses().await {
+            Ok(r) if !r.is_empty() => r,
+            Ok(_) => {
+                log::warn!("no courses found via REST API, falling back to homepage");
+                return self.scrape_courses().await;
+            }
+            Err(e) => {
+                log::warn!("fetch courses via REST API: {e:#}, falling back to homepage");
+                return self.scrape_courses().await;
+            }
+        };
+
+        // 无法根据学期判断的课程以首页 "当前学期课程" 模块为准，没有任何课程属于当前学期时同样如此
+        let current = courses::term_current(&courses, chrono::Utc::now().fixed_offset());
+        let any_current = current.contains(&Some(true));
+        let scraped = match any_current && !current.contains(&None) {
+            true => Vec::new(),
+            false => self.scrape_courses().await.unwrap_or_else(|e| {
+                log::warn!("fetch current courses from homepage: {e:#}");
+                Vec::new()
+            }),
+        };
+        for (c, cur) in courses.iter_mut().zip(current) {
+            c.is_current = match cur {
+                Some(r) if any_current => r,
+                _ => scraped.iter().any(|s| s.id == c.id && s.is_current),
+            };
+        }
+        Ok(courses)
+    }
+
+    async fn scrape_courses(&self) -> anyhow::Result<Vec<CourseMeta>> {
         let dom = self.client.bb_homepage().await?;
         let re = regex::Regex::new(r"key=([\d_]+),").unwrap();
         let portlet_sel = Selector::parse("div.portlet").unwrap();
@@ -86,7 +234,13 @@ impl Blackboard {
                 .as_str()
                 .to_owned();
 
-            Ok((key, text))
+            Ok(CourseMeta {
+                id: key,
+                long_title: text,
+                is_current: false,
+                term: None,
+                external_id: None,
+            })
         };
 
         // the first one contains the courses in the current semester
@@ -107,7 +261,7 @@ impl Blackboard {
                 let items = ul
                     .select(&sel)
                     .map(to_key_text)
-                    .map_ok(|(k, t)| (k, t, is_current))
+                    .map_ok(|c| CourseMeta { is_current, ..c })
                     .collect::<Vec<_>>();
                 log::info!("found {} courses, is_current: {is_current}", items.len());
                 courses.extend(items);
@@ -134,18 +288,11 @@ impl Blackboard {
 
         let mut courses = courses
             .into_iter()
-            .map(|(id, long_title, is_current)| {
-                Ok(CourseHandle {
-                    client: self.client.clone(),
-                    meta: CourseMeta {
-                        id,
-                        long_title,
-                        is_current,
-                    }
-                    .into(),
-                })
+            .map(|meta| CourseHandle {
+                client: self.client.clone(),
+                meta: meta.into(),
             })
-            .collect::<anyhow::Result<Vec<_>>>()?;
+            .collect::<Vec<_>>();
 
         if only_current {
             courses.retain(|c| c.meta.is_current);
@@ -155,6 +302,15 @@ impl Blackboard {
     }
 
     pub async fn user_info_id(&self) -> anyhow::Result<String> {
+        with_cache(
+            "Blackboard::user_info_id",
+            self.client.cache_ttl(),
+            self._user_info_id(),
+        )
+        .await
+    }
+
+    async fn _user_info_id(&self) -> anyhow::Result<String> {
         #[derive(Debug, Deserialize)]
         struct UserInfo {
             id: String,
@@ -171,6 +327,15 @@ impl Blackboard {
     }
 
     pub async fn user_courses(&self, user_id: &str) -> anyhow::Result<Vec<CourseEnrollment>> {
+        with_cache(
+            &format!("Blackboard::user_courses_{user_id}"),
+            self.client.cache_ttl(),
+            self._user_courses(user_id),
+        )
+        .await
+    }
+
+    async fn _user_courses(&self, user_id: &str) -> anyhow::Result<Vec<CourseEnrollment>> {
         #[derive(Debug, Deserialize)]
         struct Result {
             results: Vec<CourseEnrollment>,
@@ -190,16 +355,23 @@ impl Blackboard {
     }
 
     pub async fn course_detail(&self, course_id: &str) -> anyhow::Result<CourseDetailHandle> {
-        let val: CourseDetail = self
-            .client
-            .0
-            .http_client
-            .api_get(&format!(
-                "https://course.pku.edu.cn/learn/api/public/v1/courses/{}",
-                course_id
-            ))
-            .await
-            .context("fetch user courses")?;
+        let fut = async {
+            self.client
+                .0
+                .http_client
+                .api_get::<CourseDetail>(&format!(
+                    "https://course.pku.edu.cn/learn/api/public/v1/courses/{}",
+                    course_id
+                ))
+                .await
+                .context("fetch course detail")
+        };
+        let val = with_cache(
+            &format!("Blackboard::course_detail_{course_id}"),
+            self.client.cache_ttl(),
+            fut,
+        )
+        .await?;
         Ok(CourseDetailHandle {
             client: self.client.clone(),
             id: course_id.to_owned(),
@@ -208,7 +380,7 @@ impl Blackboard {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, serde::Serialize)]
 pub struct CourseEnrollment {
     #[serde(rename = "courseId")]
     pub course_id: String,
@@ -216,6 +388,9 @@ pub struct CourseEnrollment {
     pub course_role_id: String,
 }
 
+/// 同一门课程同时请求成绩数据的最大数量，避免触发教学网的访问频率限制
+const GRADEDATA_CONCURRENCY: usize = 8;
+
 pub struct CourseDetailHandle {
     client: Client,
     id: String,
@@ -228,84 +403,125 @@ impl CourseDetailHandle {
     }
 
     async fn gradebook_columns(&self) -> anyhow::Result<Vec<GradebookColumn>> {
-        #[derive(Debug, Deserialize)]
-        struct GradebookColumns {
-            results: Vec<GradebookColumn>,
-        }
-
-        let val: GradebookColumns = self
-            .client
-            .0
-            .http_client
-            .api_get(&format!(
-                "https://course.pku.edu.cn/learn/api/public/v2/courses/{}/gradebook/columns",
-                self.id
-            ))
-            .await
-            .context("fetch gradebook columns")?;
-        Ok(val.results)
+        self.client.gradebook_columns(&self.id).await
     }
 
     async fn gradedata(&self, column_id: &str) -> anyhow::Result<Vec<GradeUser>> {
-        #[derive(Debug, Deserialize)]
-        struct GradeUsers {
-            results: Vec<GradeUser>,
-        }
+        self.client.gradedata(&self.id, column_id).await
+    }
 
-        let val: GradeUsers = self
-            .client
-            .0
-            .http_client
-            .api_get(&format!(
-                "https://course.pku.edu.cn/learn/api/public/v2/courses/{}/gradebook/columns/{}/users",
-                self.id, column_id
-            ))
-            .await
-            .context("fetch gradebook columns")?;
-        Ok(val.results)
+    /// 获取课程的所有成绩项. `progress` 会在每获取一项成绩后以 (已完成, 总数) 调用.
+    /// 有成绩项获取失败时结果不会被缓存.
+    pub async fn all_grades(
+        &self,
+        progress: impl Fn(u64, u64),
+    ) -> anyhow::Result<Vec<GradeRecord>> {
+        with_partial_cache(
+            &format!("CourseDetailHandle::all_grades_{}", self.id),
+            self.client.cache_ttl(),
+            async {
+                let grades = self._all_grades(progress).await?;
+                let complete = grades.iter().all(|g| g.fetched);
+                Ok((grades, complete))
+            },
+        )
+        .await
     }
 
-    pub async fn all_grades(&self) -> anyhow::Result<Vec<GradeRecord>> {
-        let columns = self.gradebook_columns().await?;
+    async fn _all_grades(&self, progress: impl Fn(u64, u64)) -> anyhow::Result<Vec<GradeRecord>> {
+        use futures_util::StreamExt as _;
 
-        let mut all_grades = Vec::new();
-        for col in &columns {
-            if let Some(grading) = &col.grading
-                && grading.grading_type == "Calculated"
-                && col.name.contains("总计")
-                && !col.name.contains("平时")
-            {
-                continue;
+        let columns = self.gradebook_columns().await?;
+        let total = columns.len() as u64;
+        progress(0, total);
+        // 分类名称只用于展示，获取失败时忽略
+        let categories = match self.client.gradebook_categories(&self.id).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::debug!("fetch gradebook categories: {e:#}");
+                Vec::new()
             }
+        };
 
-            let grade_data = match self.gradedata(&col.id).await {
-                Ok(data) => data.into_iter().next(),
-                Err(_) => None,
-            };
+        let finished = std::cell::Cell::new(0);
+        let columns = futures_util::stream::iter(columns)
+            .map(async |col| {
+                let grade_data = match self.gradedata(&col.id).await {
+                    Ok(data) => Some(data.into_iter().next()),
+                    Err(e) => {
+                        log::warn!("fetch grade data of '{}': {e:#}", col.name);
+                        None
+                    }
+                };
+                finished.set(finished.get() + 1);
+                progress(finished.get(), total);
+                (col, grade_data)
+            })
+            .buffered(GRADEDATA_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut all_grades = Vec::new();
+        for (col, grade_data) in columns {
+            let calculated = col
+                .grading
+                .as_ref()
+                .is_some_and(|g| g.grading_type == "Calculated");
 
             let possible = col.score.as_ref().map(|s| s.possible).unwrap_or(0.0);
-            let score = grade_data
-                .and_then(|g| g.display_grade)
-                .and_then(|d| d.score);
+            let category = col.category_id.as_ref().and_then(|id| {
+                categories
+                    .iter()
+                    .find(|(cid, _)| cid == id)
+                    .map(|(_, title)| title.clone())
+            });
+            let description = col
+                .description
+                .as_deref()
+                .map(|d| {
+                    crate::markdown::from_element(scraper::Html::parse_fragment(d).root_element())
+                })
+                .filter(|d| !d.is_empty());
 
-            all_grades.push(GradeRecord {
+            let mut record = GradeRecord {
                 course_name: self.data.name.clone(),
-                column_name: col.name.clone(),
-                score,
+                column_name: col.name,
                 possible,
-            });
+                calculated,
+                category,
+                description,
+                due: col.grading.and_then(|g| g.due),
+                visible: col.availability.is_none_or(|a| a.available == "Yes"),
+                fetched: grade_data.is_some(),
+                ..Default::default()
+            };
+            if let Some(g) = grade_data.flatten() {
+                record.status = g.status();
+                if let Some(d) = g.display_grade {
+                    record.score = d.score;
+                    // 分数和百分比以外的成绩 (等级、完成/未完成等) 保留显示文本
+                    if !matches!(d.scale_type.as_deref(), Some("Score" | "Percent")) {
+                        record.text = d.text.filter(|t| !t.trim().is_empty());
+                    }
+                }
+            }
+            all_grades.push(record);
         }
         Ok(all_grades)
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, serde::Serialize)]
 pub struct CourseDetail {
     name: String,
     availability: Option<Availability>,
 }
 
 impl CourseDetail {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn is_available(&self) -> bool {
         self.availability
             .as_ref()
@@ -313,7 +529,7 @@ impl CourseDetail {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, serde::Serialize)]
 struct Availability {
     pub available: String,
 }
@@ -322,8 +538,30 @@ struct Availability {
 struct GradebookColumn {
     id: String,
     name: String,
+    /// 对应的课程内容 (作业、测试等)
+    #[serde(rename = "contentId")]
+    content_id: Option<String>,
     score: Option<ColumnScore>,
     grading: Option<Grading>,
+    /// HTML 格式的说明
+    description: Option<String>,
+    #[serde(rename = "gradebookCategoryId")]
+    category_id: Option<String>,
+    /// 是否对学生可见
+    availability: Option<Availability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GradebookAttempt {
+    status: Option<String>,
+    score: Option<f64>,
+}
+
+impl GradebookAttempt {
+    /// 是否是实际提交过的尝试
+    fn is_attempted(&self) -> bool {
+        self.status.as_deref() != Some("NotAttempted")
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -335,33 +573,90 @@ struct ColumnScore {
 struct Grading {
     #[serde(rename = "type")]
     grading_type: String,
+    /// ISO 8601 格式的截止时间
+    due: Option<String>,
+    /// 允许的尝试次数，0 表示不限
+    #[serde(rename = "attemptsAllowed")]
+    attempts_allowed: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
 struct GradeUser {
+    /// `Graded` 或 `NeedsGrading`
+    status: Option<String>,
+    #[serde(default)]
+    exempt: bool,
     #[serde(rename = "displayGrade")]
     display_grade: Option<DisplayGrade>,
 }
 
+impl GradeUser {
+    fn status(&self) -> GradeStatus {
+        if self.exempt {
+            GradeStatus::Exempt
+        } else if self.status.as_deref() == Some("NeedsGrading") {
+            GradeStatus::NeedsGrading
+        } else if self.display_grade.is_some() {
+            GradeStatus::Graded
+        } else {
+            GradeStatus::NotGraded
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct DisplayGrade {
+    /// `Score`, `Percent`, `Text`, `Tabular` 或 `CompleteIncomplete`
+    #[serde(rename = "scaleType")]
+    scale_type: Option<String>,
     score: Option<f64>,
+    text: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum GradeStatus {
+    Graded,
+    /// 已提交，等待批改
+    NeedsGrading,
+    /// 免除
+    Exempt,
+    #[default]
+    NotGraded,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct GradeRecord {
     pub course_name: String,
     pub column_name: String,
     pub score: Option<f64>,
     pub possible: f64,
+    /// 是否为计算列 (总计、加权总计等)
+    pub calculated: bool,
+    /// 非数值成绩 (等级、完成/未完成等) 的显示文本
+    pub text: Option<String>,
+    pub status: GradeStatus,
+    /// 成绩中心的分类 (作业、测试等)
+    pub category: Option<String>,
+    /// 成绩项说明 (Markdown)
+    pub description: Option<String>,
+    /// ISO 8601 格式的截止时间
+    pub due: Option<String>,
+    /// 是否对学生可见
+    pub visible: bool,
+    /// 成绩数据是否获取成功，失败时分数、文本和状态均未知
+    pub fetched: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct CourseMeta {
     id: String,
     long_title: String,
     /// 是否是当前学期的课程
     is_current: bool,
+    /// 从首页获取课程列表时未知
+    term: Option<CourseTerm>,
+    /// 课程的外部 ID (通常来自教务系统)，从首页获取课程列表时未知
+    external_id: Option<String>,
 }
 
 impl CourseMeta {
@@ -369,6 +664,16 @@ impl CourseMeta {
         &self.id
     }
 
+    /// 课程所属的学期
+    pub fn term(&self) -> Option<&CourseTerm> {
+        self.term.as_ref()
+    }
+
+    /// 课程的外部 ID
+    pub fn external_id(&self) -> Option<&str> {
+        self.external_id.as_deref()
+    }
+
     /// Course Name (semester)
     pub fn title(&self) -> &str {
         self.long_title
@@ -385,6 +690,48 @@ impl CourseMeta {
             .map(|(i, _)| s.split_at(i).0.trim())
             .unwrap_or(s)
     }
+
+    pub fn matches(&self, sel: &CourseSelector) -> bool {
+        sel.matches(&self.id, &self.long_title)
+    }
+}
+
+/// 课程选择器：课程 ID (形如 `_12345_1`)，或课程标题的子串/正则表达式 (不区分大小写)
+#[derive(Debug, Clone)]
+pub enum CourseSelector {
+    Id(String),
+    Title(String, Option<regex::Regex>),
+}
+
+impl std::str::FromStr for CourseSelector {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        static RE_ID: std::sync::LazyLock<regex::Regex> =
+            std::sync::LazyLock::new(|| regex::Regex::new(r"^_\d+_\d+$").unwrap());
+        if RE_ID.is_match(s) {
+            return Ok(Self::Id(s.to_owned()));
+        }
+        // 不是合法正则表达式时只按子串匹配
+        let re = regex::RegexBuilder::new(s)
+            .case_insensitive(true)
+            .build()
+            .ok();
+        Ok(Self::Title(s.to_owned(), re))
+    }
+}
+
+impl CourseSelector {
+    /// 课程 ID 完全相同，或课程标题包含该子串/匹配该正则表达式
+    pub fn matches(&self, id: &str, title: &str) -> bool {
+        match self {
+            Self::Id(i) => i == id,
+            Self::Title(s, re) => {
+                title.contains(s.as_str()) || re.as_ref().is_some_and(|re| re.is_match(title))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -398,11 +745,11 @@ impl CourseHandle {
         &self.meta.id
     }
 
-    pub fn long_title(&self) -> &str {
-        &self.meta.long_title
+    pub fn meta(&self) -> &CourseMeta {
+        &self.meta
     }
 
-    pub async fn _get(&self) -> anyhow::Result<HashMap<String, String>> {
+    pub async fn _get(&self) -> anyhow::Result<Vec<(String, String)>> {
         let dom = self.client.bb_coursepage(&self.meta.id).await?;
 
         let entries = dom
@@ -412,7 +759,7 @@ impl CourseHandle {
                 let href = a.value().attr("href").unwrap();
                 Ok((text, href.to_owned()))
             })
-            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         Ok(entries)
     }
@@ -439,7 +786,8 @@ impl CourseHandle {
 pub struct Course {
     client: Client,
     meta: Arc<CourseMeta>,
-    entries: HashMap<String, String>,
+    /// 课程菜单项 (名称, 链接)，按菜单中的顺序排列
+    entries: Vec<(String, String)>,
 }
 
 impl Course {
@@ -456,8 +804,8 @@ impl Course {
             self.client.clone(),
             self.meta.clone(),
             self.entries()
-                .values()
-                .filter_map(|uri| {
+                .iter()
+                .filter_map(|(name, uri)| {
                     let url = low_level::convert_uri(uri).ok()?.into_url().ok()?;
                     if !low_level::blackboard::LIST_CONTENT.ends_with(url.path()) {
                         return None;
@@ -465,7 +813,7 @@ impl Course {
 
                     let (_, content_id) = url.query_pairs().find(|(k, _)| k == "content_id")?;
 
-                    Some(content_id.to_string())
+                    Some((content_id.to_string(), name.trim().to_owned()))
                 })
                 .collect(),
         )
@@ -479,11 +827,11 @@ impl Course {
         }
     }
 
-    pub fn entries(&self) -> &HashMap<String, String> {
+    pub fn entries(&self) -> &[(String, String)] {
         &self.entries
     }
 
-    /// 直接从课程公告页抓取课程公告。
+    /// 直接从课程公告页抓取课程公告，仅在 REST API 不可用时使用 (见 [`Course::list_announcements`]).
     pub async fn list_announcements_from_coursepage(
         &self,
     ) -> anyhow::Result<Vec<CourseAnnouncementHandle>> {
@@ -521,6 +869,7 @@ impl Course {
                     let mut sibling = h3.next_sibling();
                     let mut content = String::new();
                     let mut time = String::new();
+                    let mut attachments = Vec::new();
 
                     for _ in 0..10 {
                         let Some(sib) = sibling else {
@@ -535,21 +884,25 @@ impl Course {
                                 break;
                             }
 
+                            let links = collect_file_links(el_ref, "a[href]", &attachments);
+                            attachments.extend(links);
+
                             let text = el_ref.text().collect::<String>();
                             if tag == "p" && text.contains("发布") {
                                 time = text.trim().to_string();
                             } else if (tag == "div" || tag == "p") && !text.trim().is_empty() {
                                 if !content.is_empty() {
-                                    content.push('\n');
+                                    content.push_str("\n\n");
                                 }
-                                content.push_str(&text);
+                                content.push_str(&crate::markdown::from_element(el_ref));
                             }
                         }
 
                         sibling = sib.next_sibling();
                     }
 
-                    parsed_announcements.push((title, content, time));
+                    let native_id = native_content_id(h3);
+                    parsed_announcements.push((title, content, time, attachments, native_id));
                 }
             } else {
                 let content = container.text().collect::<String>().trim().to_string();
@@ -574,7 +927,9 @@ impl Course {
                     } else {
                         title
                     };
-                    parsed_announcements.push((title, content, time));
+                    let attachments = collect_file_links(container, "a[href]", &[]);
+                    let native_id = native_content_id(container);
+                    parsed_announcements.push((title, content, time, attachments, native_id));
                 }
             }
         }
@@ -582,7 +937,7 @@ impl Course {
         let mut announcements = Vec::new();
         let mut seen_titles = HashSet::new();
 
-        for (idx, (title, content, time)) in parsed_announcements.iter().enumerate() {
+        for (title, content, time, attachments, native_id) in &parsed_announcements {
             if title.is_empty() || title.len() < 5 {
                 continue;
             }
@@ -602,7 +957,17 @@ impl Course {
                 continue;
             }
 
-            let id = format!("{}_{}", self.meta.id, idx);
+            // 优先使用页面中的内容 ID；没有时以标题和时间生成 id (不含正文，
+            // 使公告被编辑后仍保持不变)
+            let id = match native_id {
+                Some(id) => id.clone(),
+                None => {
+                    let mut hasher = std::hash::DefaultHasher::new();
+                    normalize_compact_text(title).hash(&mut hasher);
+                    normalize_compact_text(time).hash(&mut hasher);
+                    format!("{}_{:x}", self.meta.id, hasher.finish())
+                }
+            };
             let content_data = CourseContentData {
                 id: id.clone(),
                 title: title.clone(),
@@ -610,20 +975,24 @@ impl Course {
                 has_link: false,
                 descriptions: if !content.is_empty() {
                     content
-                        .lines()
+                        .split("\n\n")
                         .map(str::trim)
-                        .filter(|line| !line.is_empty())
+                        .filter(|p| !p.is_empty())
                         .map(ToOwned::to_owned)
                         .collect()
                 } else {
                     vec![]
                 },
-                attachments: vec![],
+                attachments: attachments.clone(),
+                embedded: vec![],
                 time: if !time.is_empty() {
                     Some(time.clone())
                 } else {
                     None
                 },
+                author: None,
+                parent: None,
+                path: vec![],
             };
 
             announcements.push(CourseAnnouncementHandle {
@@ -649,12 +1018,19 @@ pub struct CourseContentStream {
     course: Arc<CourseMeta>,
     visited_ids: HashSet<String>,
     probe_ids: Vec<String>,
+    /// 待访问的内容页所对应的父节点 id 与路径 (以课程菜单项开头)
+    containers: HashMap<String, (Option<String>, Vec<String>)>,
 }
 
 impl CourseContentStream {
-    fn new(client: Client, course: Arc<CourseMeta>, probe_ids: Vec<String>) -> Self {
+    /// `probe_ids` 为课程菜单中的内容页 id 及其菜单项名称
+    fn new(client: Client, course: Arc<CourseMeta>, probe_ids: Vec<(String, String)>) -> Self {
         // implicitly deduplicate probe_ids
-        let visited_ids = HashSet::from_iter(probe_ids);
+        let containers = probe_ids
+            .into_iter()
+            .map(|(id, menu)| (id, (None, vec![menu])))
+            .collect::<HashMap<_, _>>();
+        let visited_ids = containers.keys().cloned().collect::<HashSet<_>>();
         let probe_ids = visited_ids.iter().cloned().collect();
         Self {
             batch_size: 8,
@@ -662,6 +1038,7 @@ impl CourseContentStream {
             course,
             visited_ids,
             probe_ids,
+            containers,
         }
     }
     async fn try_next_batch(&mut self, ids: &[String]) -> anyhow::Result<Vec<CourseContentData>> {
@@ -673,32 +1050,40 @@ impl CourseContentStream {
         let doms = futures_util::future::join_all(futs).await;
 
         let mut all_contents = Vec::new();
-        for dom in doms {
+        for (id, dom) in ids.iter().zip(doms) {
             let dom = dom?;
+            let (parent, path) = self.containers.get(id).cloned().unwrap_or_default();
             let selector = Selector::parse("#content_listContainer > li").unwrap();
-            let contents = dom
-                .select(&selector)
-                .filter_map(|li| {
-                    CourseContentData::from_element(li)
-                        .inspect_err(|e| log::warn!("CourseContentData::from_element error: {e}"))
-                        .ok()
-                })
+            let contents = dom.select(&selector).filter_map(|li| {
+                CourseContentData::from_element(li)
+                    .inspect_err(|e| log::warn!("CourseContentData::from_element error: {e}"))
+                    .ok()
+            });
+
+            for mut data in contents {
                 // filter out visited ids
-                .filter(|data| self.visited_ids.insert(data.id.to_owned()))
-                // add the rest new ids to probe_ids
-                .inspect(|data| {
-                    log::debug!(
-                        "find new content {:?}, title = {}, kind = {:?}",
-                        data.id,
-                        data.title,
-                        data.kind
-                    );
-                    if data.has_link {
-                        self.probe_ids.push(data.id.to_owned())
-                    }
-                });
+                if !self.visited_ids.insert(data.id.to_owned()) {
+                    continue;
+                }
+                log::debug!(
+                    "find new content {:?}, title = {}, kind = {:?}",
+                    data.id,
+                    data.title,
+                    data.kind
+                );
+                data.parent = parent.clone();
+                data.path = path.clone();
 
-            all_contents.extend(contents);
+                // add the rest new ids to probe_ids
+                if data.has_link {
+                    let mut child_path = path.clone();
+                    child_path.push(data.title.clone());
+                    self.containers
+                        .insert(data.id.clone(), (Some(data.id.clone()), child_path));
+                    self.probe_ids.push(data.id.to_owned())
+                }
+                all_contents.push(data);
+            }
         }
 
         Ok(all_contents)
@@ -758,6 +1143,30 @@ impl std::str::FromStr for CourseContentID {
     }
 }
 
+/// 课程内容的路径前缀，如 `课件/第三周`：以课程菜单项开头，各级用 `/` 分隔
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentPath(Vec<String>);
+
+impl std::str::FromStr for ContentPath {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ContentPath(
+            s.split('/')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+        ))
+    }
+}
+
+impl ContentPath {
+    /// 路径 `path` 是否位于此路径下 (包括相同的路径)
+    pub fn contains(&self, path: &[String]) -> bool {
+        path.len() >= self.0.len() && path.iter().zip(&self.0).all(|(a, b)| a == b)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CourseContent {
     client: Client,
@@ -785,10 +1194,37 @@ impl CourseContent {
         &self.data.attachments
     }
 
+    /// 正文中嵌入的文件和图片 (文件名, 链接)
+    pub fn embedded(&self) -> &[(String, String)] {
+        &self.data.embedded
+    }
+
     pub fn descriptions(&self) -> &[String] {
         &self.data.descriptions
     }
 
+    /// 所在文件夹的 content id，位于课程菜单页顶层时为 `None`
+    pub fn parent_id(&self) -> Option<&str> {
+        self.data.parent.as_deref()
+    }
+
+    /// 所在位置的路径，以所属的课程菜单项开头，依次为各级文件夹标题
+    pub fn path(&self) -> &[String] {
+        &self.data.path
+    }
+
+    pub fn into_quiz_opt(self) -> Option<CourseQuizHandle> {
+        if let CourseContentKind::Quiz = self.data.kind {
+            Some(CourseQuizHandle {
+                client: self.client,
+                course: self.course,
+                content: self.data,
+            })
+        } else {
+            None
+        }
+    }
+
     pub fn into_assignment_opt(self) -> Option<CourseAssignmentHandle> {
         if let CourseContentKind::Assignment = self.data.kind {
             Some(CourseAssignmentHandle {
@@ -820,30 +1256,92 @@ pub struct CourseContentData {
     title: String,
     kind: CourseContentKind,
     has_link: bool,
+    /// 描述正文，每项为一段 Markdown
     descriptions: Vec<String>,
     attachments: Vec<(String, String)>,
+    /// 正文中嵌入的教学网文件链接和图片
+    #[serde(default)]
+    embedded: Vec<(String, String)>,
     #[serde(skip_serializing_if = "Option::is_none")]
     time: Option<String>,
+    /// 发布者 (目前只有公告有)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    /// 父文件夹的 content id
+    #[serde(default)]
+    parent: Option<String>,
+    /// 课程菜单项及各级文件夹标题
+    #[serde(default)]
+    path: Vec<String>,
 }
 
-fn collect_text(element: scraper::ElementRef) -> String {
-    let mut text_content = String::new();
-    for node_ref in element.children() {
-        match node_ref.value() {
-            scraper::node::Node::Text(text) if !text.trim().is_empty() => {
-                text_content.push_str(text);
-            }
-            scraper::node::Node::Element(el) => {
-                if el.name() != "script"
-                    && let Some(child_element) = scraper::ElementRef::wrap(node_ref)
-                {
-                    text_content.push_str(&collect_text(child_element));
-                }
-            }
-            _ => {}
+/// 收集正文 (`div.vtbegenerated`) 中指向教学网文件 (`bbcswebdav`) 的链接和图片,
+/// 跳过已经出现在附件列表中的链接. 返回 (文件名, 链接)
+fn collect_embedded(
+    detail_div: scraper::ElementRef<'_>,
+    attachments: &[(String, String)],
+) -> Vec<(String, String)> {
+    collect_file_links(
+        detail_div,
+        "div.vtbegenerated a[href], div.vtbegenerated img[src]",
+        attachments,
+    )
+}
+
+/// 收集 `root` 下匹配 `selector` 的 `a[href]`/`img[src]` 中指向教学网文件的链接，
+/// 跳过 `skip` 中已有的链接. 返回 (文件名, 链接)
+fn collect_file_links(
+    root: scraper::ElementRef<'_>,
+    selector: &str,
+    skip: &[(String, String)],
+) -> Vec<(String, String)> {
+    let sel = Selector::parse(selector).unwrap();
+    let mut seen = skip
+        .iter()
+        .map(|(_, href)| href.as_str())
+        .collect::<HashSet<_>>();
+    let mut res = Vec::new();
+    for el in root.select(&sel) {
+        let (href, label) = if el.value().name() == "img" {
+            let label = el.value().attr("alt").unwrap_or_default().to_owned();
+            (el.value().attr("src").unwrap_or_default().trim(), label)
+        } else {
+            (
+                el.value().attr("href").unwrap_or_default().trim(),
+                el.text().collect::<String>(),
+            )
+        };
+        if !href.contains("/bbcswebdav/") || !seen.insert(href) {
+            continue;
         }
+
+        // 以链接中的文件名为准，图片的 alt 和链接文字往往不带扩展名
+        let path = href.split(['?', '#']).next().unwrap_or_default();
+        let basename = path.rsplit('/').next().unwrap_or_default();
+        let basename = percent_encoding::percent_decode(basename.as_bytes())
+            .decode_utf8_lossy()
+            .to_string();
+        let label = label.trim();
+        let name = if basename.contains('.') || label.is_empty() {
+            basename
+        } else {
+            label.to_owned()
+        };
+        let name = name.replace(['/', '\\'], "_");
+        res.push((name, href.to_owned()));
     }
-    text_content
+    res
+}
+
+/// 元素自身或最近的祖先元素上形如 `_12345_1` 的内容 ID
+fn native_content_id(el: scraper::ElementRef<'_>) -> Option<String> {
+    static RE: std::sync::LazyLock<regex::Regex> =
+        std::sync::LazyLock::new(|| regex::Regex::new(r"^_\d+_\d+$").unwrap());
+    std::iter::once(*el)
+        .chain(el.ancestors())
+        .filter_map(|n| n.value().as_element()?.attr("id"))
+        .find(|id| RE.is_match(id))
+        .map(ToOwned::to_owned)
 }
 
 fn normalize_compact_text(s: &str) -> String {
@@ -897,7 +1395,8 @@ impl CourseContentData {
 
         let descriptions = detail_div
             .select(&Selector::parse("div.vtbegenerated > *").unwrap())
-            .map(|p| collect_text(p).trim().to_owned())
+            .map(crate::markdown::from_element)
+            .filter(|p| !p.is_empty())
             .collect::<Vec<_>>();
 
         let mut attachments = detail_div
@@ -930,6 +1429,8 @@ impl CourseContentData {
 
         attachments.extend(audio);
 
+        let embedded = collect_embedded(detail_div, &attachments);
+
         Ok(CourseContentData {
             id,
             title,
@@ -937,7 +1438,11 @@ impl CourseContentData {
             has_link,
             descriptions,
             attachments,
+            embedded,
             time: None,
+            author: None,
+            parent: None,
+            path: vec![],
         })
     }
 }
@@ -976,10 +1481,31 @@ impl CourseAssignmentHandle {
                     .join(" ")
             });
 
+        // 页面上的截止时间无法解析时，使用成绩中心中对应成绩项的截止时间
+        let deadline = match deadline {
+            Some(d) if Deadline::parse(&d).is_some() => Some(d),
+            d => self.gradebook_due().await.or(d),
+        };
+
         let attempt = self._get_current_attempt().await?;
 
         Ok(CourseAssignmentData { deadline, attempt })
     }
+
+    async fn gradebook_due(&self) -> Option<String> {
+        let columns = match self.client.gradebook_columns(&self.course.id).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::debug!("fetch gradebook columns: {e:#}");
+                return None;
+            }
+        };
+        columns
+            .into_iter()
+            .find(|c| c.content_id.as_deref() == Some(self.content.id.as_str()))
+            .and_then(|c| c.grading?.due)
+    }
+
     pub async fn get(&self) -> anyhow::Result<CourseAssignment> {
         let data = with_cache(
             &format!(
@@ -1005,22 +1531,34 @@ impl CourseAssignmentHandle {
             .bb_course_assignment_viewpage(&self.course.id, &self.content.id)
             .await?;
 
-        let attempt_label = if let Some(e) = dom
-            .select(&Selector::parse("h3#currentAttempt_label").unwrap())
-            .next()
-        {
-            e.text().collect::<String>()
-        } else {
-            return Ok(None);
-        };
+        Ok(parse_current_attempt(&dom).map(|(label, _)| label))
+    }
+}
 
-        let attempt_label = attempt_label
-            .split_whitespace()
-            .collect::<Vec<_>>()
-            .join(" ");
+/// 提交记录的快照：页面上当前提交记录的标签和 ID，以及页面中没有 ID 时成绩中心记录的提交次数
+#[derive(Debug, Clone, PartialEq)]
+struct AttemptSnapshot {
+    label: String,
+    id: Option<String>,
+    count: Option<usize>,
+}
 
-        Ok(Some(attempt_label))
-    }
+/// 解析作业页面上当前提交记录的标签和 ID (页面中没有 ID 时为 `None`)
+fn parse_current_attempt(dom: &scraper::Html) -> Option<(String, Option<String>)> {
+    let label = dom
+        .select(&Selector::parse("h3#currentAttempt_label").unwrap())
+        .next()?
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let id = dom
+        .select(&Selector::parse(r#"input[name="attempt_id"]"#).unwrap())
+        .filter_map(|e| e.value().attr("value"))
+        .find(|v| !v.is_empty())
+        .map(ToOwned::to_owned);
+    Some((label, id))
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -1052,6 +1590,11 @@ impl CourseAssignment {
         &self.content.attachments
     }
 
+    /// 正文中嵌入的文件和图片 (文件名, 链接)
+    pub fn embedded(&self) -> &[(String, String)] {
+        &self.content.embedded
+    }
+
     pub fn last_attempt(&self) -> Option<&str> {
         self.data.attempt.as_deref()
     }
@@ -1081,15 +1624,13 @@ impl CourseAssignment {
         Ok(submitformfields)
     }
 
-    pub async fn submit_file(&self, path: &std::path::Path) -> anyhow::Result<()> {
+    /// 以 `content_type` 提交文件，并在提交后重新拉取作业页面校验服务端确实收到了同样的文件.
+    pub async fn submit_file(
+        &self,
+        path: &std::path::Path,
+        content_type: &str,
+    ) -> anyhow::Result<SubmissionReceipt> {
         log::info!("submitting file: {}", path.display());
-
-        let ext = path
-            .extension()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-        let content_type = get_mime_type(&ext);
         log::info!("content type: {content_type}");
 
         let filename = path
@@ -1098,6 +1639,14 @@ impl CourseAssignment {
             .to_string_lossy()
             .to_string();
 
+        // 记录提交前的提交记录，用于确认提交后出现了新的记录
+        let dom = self
+            .client
+            .bb_course_assignment_viewpage(&self.course.id, &self.content.id)
+            .await?;
+        let before = self.attempt_snapshot(&dom).await;
+        log::debug!("attempt before submission: {before:?}");
+
         let map = self.get_submit_formfields().await?;
         log::trace!("map: {map:#?}");
 
@@ -1162,58 +1711,151 @@ impl CourseAssignment {
             anyhow::bail!("invalid status {}", st);
         }
 
-        Ok(())
+        let server_time = res
+            .headers()
+            .get(http::header::DATE)
+            .and_then(|v| v.to_str().ok())
+            .map(ToOwned::to_owned);
+
+        let data = compio::fs::read(path).await?;
+        let sha256 = sha256_hex(&data);
+
+        let attempt = self
+            .verify_submission(before.as_ref(), &filename, &sha256)
+            .await
+            .with_context(|| format!("verify submission of '{filename}'"))?;
+
+        Ok(SubmissionReceipt {
+            course_id: self.course.id.clone(),
+            content_id: self.content.id.clone(),
+            assignment: self.content.title.clone(),
+            file: filename,
+            size: data.len() as u64,
+            sha256,
+            attempt,
+            server_time,
+        })
     }
 
-    /// Try to parse the deadline string into a NaiveDateTime.
-    pub fn deadline(&self) -> Option<chrono::DateTime<chrono::Local>> {
-        let d = self.data.deadline.as_deref()?;
-        let re = regex::Regex::new(
-            r"(\d{4})年(\d{1,2})月(\d{1,2})日 星期. (上午|下午)(\d{1,2}):(\d{1,2})",
-        )
-        .unwrap();
-
-        if let Some(caps) = re.captures(d) {
-            let year: i32 = caps[1].parse().ok()?;
-            let month: u32 = caps[2].parse().ok()?;
-            let day: u32 = caps[3].parse().ok()?;
-            let mut hour: u32 = caps[5].parse().ok()?;
-            let minute: u32 = caps[6].parse().ok()?;
-
-            // Adjust for PM times
-            if &caps[4] == "下午" && hour < 12 {
-                hour += 12;
-            }
+    /// 解析页面上的当前提交记录. 页面中没有提交记录的 ID 时，从成绩中心获取提交次数.
+    async fn attempt_snapshot(&self, dom: &scraper::Html) -> Option<AttemptSnapshot> {
+        let (label, id) = parse_current_attempt(dom)?;
+        let count = match id {
+            Some(_) => None,
+            None => self.attempt_count().await.unwrap_or_else(|e| {
+                log::debug!("fetch attempt count of {}: {e:#}", self.content.title);
+                None
+            }),
+        };
+        Some(AttemptSnapshot { label, id, count })
+    }
 
-            // Create NaiveDateTime
-            let naive_dt = chrono::NaiveDateTime::new(
-                chrono::NaiveDate::from_ymd_opt(year, month, day)?,
-                chrono::NaiveTime::from_hms_opt(hour, minute, 0)?,
-            );
+    /// 成绩中心中记录的提交次数，作业没有对应的成绩列时为 `None`
+    async fn attempt_count(&self) -> anyhow::Result<Option<usize>> {
+        let columns = self.client.gradebook_columns(&self.course.id).await?;
+        let Some(col) = columns
+            .iter()
+            .find(|c| c.content_id.as_deref() == Some(self.content.id.as_str()))
+        else {
+            return Ok(None);
+        };
+        let attempts = self
+            .client
+            .gradebook_attempts(&self.course.id, &col.id)
+            .await?;
+        Ok(Some(attempts.iter().filter(|a| a.is_attempted()).count()))
+    }
 
-            let r = chrono::Local.from_local_datetime(&naive_dt).unwrap();
+    /// 重新获取作业的提交记录页面，确认当前提交记录是提交前的记录 `before` 之后的新记录且包含
+    /// `filename`，并将服务端保存的文件与本地文件的 sha256 进行比对. 返回当前提交记录的标签.
+    async fn verify_submission(
+        &self,
+        before: Option<&AttemptSnapshot>,
+        filename: &str,
+        sha256: &str,
+    ) -> anyhow::Result<String> {
+        let dom = self
+            .client
+            .bb_course_assignment_viewpage(&self.course.id, &self.content.id)
+            .await?;
 
-            Some(r)
-        } else {
-            None
+        let current = self
+            .attempt_snapshot(&dom)
+            .await
+            .context("no attempt found after submission")?;
+        log::debug!("attempt after submission: {current:?}");
+        anyhow::ensure!(
+            before.is_none_or(|b| is_new_attempt(b, &current)),
+            "no new attempt found after submission (current attempt: '{}')",
+            current.label
+        );
+        let attempt = current.label;
+
+        let uri = dom
+            .select(&Selector::parse("a[href*=\"/webapps/assignment/download\"]").unwrap())
+            .find(|a| a.text().collect::<String>().trim() == filename)
+            .and_then(|a| a.value().attr("href"))
+            .with_context(|| format!("file '{filename}' not found in attempt '{attempt}'"))?
+            .to_owned();
+
+        let mut res = self.client.get_by_uri(&uri).await?;
+        while let Ok(url) = low_level::extract_redirect_url(&res) {
+            log::debug!("submitted file redirected to {url}");
+            res = self.client.get_by_uri(url).await?;
         }
+        anyhow::ensure!(res.status().is_success(), "status not success");
+
+        let remote = res.bytes().await?;
+        let remote_sha256 = sha256_hex(&remote);
+        anyhow::ensure!(
+            remote_sha256 == sha256,
+            "checksum mismatch: local {sha256}, server {remote_sha256} ({} bytes)",
+            remote.len()
+        );
+
+        Ok(attempt)
+    }
+
+    /// Try to parse the deadline string (as Beijing time).
+    pub fn deadline(&self) -> Option<Deadline> {
+        Deadline::parse(self.data.deadline.as_deref()?)
     }
 
     pub fn deadline_raw(&self) -> Option<&str> {
         self.data.deadline.as_deref()
     }
+}
 
-    pub async fn download_attachment(
-        &self,
-        uri: &str,
-        dest: &std::path::Path,
-    ) -> anyhow::Result<()> {
-        self.client
-            .course_attachment_download(uri, dest, true)
-            .await
+/// 作业提交并校验成功后的回执
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SubmissionReceipt {
+    pub course_id: String,
+    pub content_id: String,
+    pub assignment: String,
+    pub file: String,
+    pub size: u64,
+    pub sha256: String,
+    /// 服务端显示的提交记录标签
+    pub attempt: String,
+    /// 提交请求响应的 `Date` 头
+    pub server_time: Option<String>,
+}
+
+/// `current` 是否是 `before` 之后的新提交记录：都有 ID 时比较 ID，否则比较提交次数，
+/// 都无法获取时才比较标签 (标签只精确到分钟). 文件内容另外通过 sha256 校验.
+fn is_new_attempt(before: &AttemptSnapshot, current: &AttemptSnapshot) -> bool {
+    match (&before.id, &current.id, before.count, current.count) {
+        (Some(x), Some(y), _, _) => x != y,
+        (_, _, Some(x), Some(y)) => y > x,
+        _ => before.label != current.label,
     }
 }
 
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::Digest as _;
+    format!("{:x}", sha2::Sha256::digest(data))
+}
+
 #[derive(Debug, Clone)]
 pub struct CourseAnnouncementHandle {
     course: Arc<CourseMeta>,
@@ -1221,6 +1863,7 @@ pub struct CourseAnnouncementHandle {
 }
 
 impl CourseAnnouncementHandle {
+    /// 用于显示的短 ID. 哈希算法不保证跨版本稳定，需要持久保存时使用 [`Self::key`]
     pub fn id(&self) -> String {
         let mut hasher = std::hash::DefaultHasher::new();
         self.course.id.hash(&mut hasher);
@@ -1229,14 +1872,30 @@ impl CourseAnnouncementHandle {
         format!("{x:x}")
     }
 
+    /// 稳定的标识 `course_id:content_id`
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.course.id, self.content.id)
+    }
+
     pub fn title(&self) -> &str {
         &self.content.title
     }
 
-    pub fn time(&self) -> Option<&str> {
+    /// 发布时间
+    pub fn time(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        Deadline::parse(self.content.time.as_deref()?).map(|t| t.time())
+    }
+
+    /// 页面上或 REST API 给出的原始发布时间
+    pub fn time_raw(&self) -> Option<&str> {
         self.content.time.as_deref()
     }
 
+    /// 发布者姓名
+    pub fn author(&self) -> Option<&str> {
+        self.content.author.as_deref()
+    }
+
     pub fn descriptions(&self) -> &[String] {
         &self.content.descriptions
     }
@@ -1244,43 +1903,11 @@ impl CourseAnnouncementHandle {
     pub fn attachments(&self) -> &[(String, String)] {
         &self.content.attachments
     }
-}
 
-/// 根据文件扩展名返回对应的 MIME 类型
-pub fn get_mime_type(extension: &str) -> &str {
-    let mime_types: HashMap<&str, &str> = [
-        ("html", "text/html"),
-        ("htm", "text/html"),
-        ("txt", "text/plain"),
-        ("csv", "text/csv"),
-        ("json", "application/json"),
-        ("xml", "application/xml"),
-        ("png", "image/png"),
-        ("jpg", "image/jpeg"),
-        ("jpeg", "image/jpeg"),
-        ("gif", "image/gif"),
-        ("bmp", "image/bmp"),
-        ("webp", "image/webp"),
-        ("mp3", "audio/mpeg"),
-        ("wav", "audio/wav"),
-        ("mp4", "video/mp4"),
-        ("avi", "video/x-msvideo"),
-        ("pdf", "application/pdf"),
-        ("zip", "application/zip"),
-        ("tar", "application/x-tar"),
-        ("7z", "application/x-7z-compressed"),
-        ("rar", "application/vnd.rar"),
-        ("exe", "application/octet-stream"),
-        ("bin", "application/octet-stream"),
-    ]
-    .iter()
-    .cloned()
-    .collect();
-
-    mime_types
-        .get(extension)
-        .copied()
-        .unwrap_or("application/octet-stream")
+    /// 正文中嵌入的图片 (文件名, 链接)
+    pub fn embedded(&self) -> &[(String, String)] {
+        &self.content.embedded
+    }
 }
 
 #[cfg(test)]
@@ -1288,11 +1915,99 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_get_mime_type() {
-        assert_eq!(get_mime_type("html"), "text/html");
-        assert_eq!(get_mime_type("png"), "image/png");
-        assert_eq!(get_mime_type("mp3"), "audio/mpeg");
-        assert_eq!(get_mime_type("unknown"), "application/octet-stream");
+    fn test_parse_current_attempt() {
+        let html = r#"<h3 id="currentAttempt_label">尝试
+            25-10-19 下午3:00</h3>
+            <form><input type="hidden" name="attempt_id" value="_123_1"></form>"#;
+        let cur = parse_current_attempt(&scraper::Html::parse_document(html)).unwrap();
+        assert_eq!(
+            cur,
+            (
+                "尝试 25-10-19 下午3:00".to_owned(),
+                Some("_123_1".to_owned())
+            )
+        );
+
+        let snapshot = |id: Option<&str>, count| AttemptSnapshot {
+            label: "尝试 25-10-19 下午3:00".to_owned(),
+            id: id.map(ToOwned::to_owned),
+            count,
+        };
+        assert!(is_new_attempt(
+            &snapshot(Some("_122_1"), None),
+            &snapshot(Some("_123_1"), None)
+        ));
+        assert!(!is_new_attempt(
+            &snapshot(Some("_123_1"), None),
+            &snapshot(Some("_123_1"), None)
+        ));
+        // 同一分钟内的两次提交标签相同，以提交次数为准
+        assert!(is_new_attempt(
+            &snapshot(None, Some(1)),
+            &snapshot(None, Some(2))
+        ));
+        assert!(!is_new_attempt(
+            &snapshot(None, Some(2)),
+            &snapshot(None, Some(2))
+        ));
+        assert!(!is_new_attempt(
+            &snapshot(None, None),
+            &snapshot(None, None)
+        ));
+        assert!(parse_current_attempt(&scraper::Html::parse_document("<p>未提交</p>")).is_none());
+    }
+
+    #[test]
+    fn test_content_path() {
+        let path = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let p: ContentPath = " 课件 / 第三周/".parse().unwrap();
+        assert_eq!(p, ContentPath(path(&["课件", "第三周"])));
+        assert!(p.contains(&path(&["课件", "第三周"])));
+        assert!(p.contains(&path(&["课件", "第三周", "习题"])));
+        assert!(!p.contains(&path(&["课件"])));
+        assert!(!p.contains(&path(&["课件", "第三周习题"])));
+        assert!(!p.contains(&path(&["作业", "第三周"])));
+
+        let all: ContentPath = "/".parse().unwrap();
+        assert!(all.contains(&[]));
+        assert!(all.contains(&path(&["课件"])));
+    }
+
+    #[test]
+    fn test_collect_embedded() {
+        let html = r#"<div class="details"><div class="vtbegenerated">
+            <p>见 <a href="/bbcswebdav/pid-1-dt-content-rid-2_1/xid-2_1">第一讲讲义</a></p>
+            <p><a href="/bbcswebdav/pid-1-dt-content-rid-3_1/courses/X/hw1.pdf">作业一</a>
+               <a href="https://example.com/">外部链接</a>
+               <a href="/bbcswebdav/pid-1-dt-content-rid-4_1/xid-4_1">重复的附件</a></p>
+            <p><img src="/bbcswebdav/pid-1-dt-content-rid-5_1/xid-5_1%E5%9B%BE.png" alt="示意图"></p>
+        </div></div>"#;
+        let dom = scraper::Html::parse_fragment(html);
+        let div = dom
+            .select(&Selector::parse("div.details").unwrap())
+            .next()
+            .unwrap();
+        let atts = vec![(
+            "附件.pdf".to_owned(),
+            "/bbcswebdav/pid-1-dt-content-rid-4_1/xid-4_1".to_owned(),
+        )];
+        let names = collect_embedded(div, &atts)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["第一讲讲义", "hw1.pdf", "xid-5_1图.png"]);
+    }
+
+    #[test]
+    fn test_native_content_id() {
+        let html = r#"<ul><li id="_4567_1"><div class="item"><h3>期中考试安排</h3></div></li>
+            <li id="anonymous_element_8"><h3>无 ID</h3></li></ul>"#;
+        let dom = scraper::Html::parse_fragment(html);
+        let ids = dom
+            .select(&Selector::parse("h3").unwrap())
+            .map(native_content_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Some("_4567_1".to_owned()), None]);
     }
 
     #[test]
@@ -1310,4 +2025,40 @@ mod tests {
 
         assert_eq!(k1, k2);
     }
+
+    #[test]
+    fn test_grade_user_status() {
+        let parse = |s: &str| serde_json::from_str::<GradeUser>(s).unwrap();
+
+        let g = parse(
+            r#"{"userId": "_1_1", "status": "Graded",
+                "displayGrade": {"scaleType": "Tabular", "score": 93.0, "text": "A"}}"#,
+        );
+        assert_eq!(g.status(), GradeStatus::Graded);
+        let d = g.display_grade.unwrap();
+        assert_eq!(d.scale_type.as_deref(), Some("Tabular"));
+        assert_eq!((d.score, d.text.as_deref()), (Some(93.0), Some("A")));
+
+        let g = parse(r#"{"userId": "_1_1", "status": "NeedsGrading"}"#);
+        assert_eq!(g.status(), GradeStatus::NeedsGrading);
+        let g = parse(r#"{"userId": "_1_1", "exempt": true}"#);
+        assert_eq!(g.status(), GradeStatus::Exempt);
+        let g = parse(r#"{"userId": "_1_1"}"#);
+        assert_eq!(g.status(), GradeStatus::NotGraded);
+    }
+
+    #[test]
+    fn test_course_selector() {
+        let sel = |s: &str| s.parse::<CourseSelector>().unwrap();
+        let title = "25019-001: 高等数学(A)(二)(24-25学年第2学期)";
+
+        assert!(sel("_12345_1").matches("_12345_1", title));
+        assert!(!sel("_12345_1").matches("_12346_1", title));
+        assert!(sel("高等数学(A)").matches("_1_1", title));
+        assert!(sel("高等数学.*二").matches("_1_1", title));
+        assert!(sel(r"^25019").matches("_1_1", title));
+        assert!(!sel("线性代数|数学分析").matches("_1_1", title));
+        // 不合法的正则表达式按子串匹配
+        assert!(sel("(二").matches("_1_1", title));
+    }
 }
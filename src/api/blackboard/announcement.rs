@@ -0,0 +1,194 @@
+use super::{
+    API_BASE, Course, CourseAnnouncementHandle, CourseContentData, CourseContentKind,
+    collect_file_links, person_name,
+};
+use crate::utils::with_cache;
+use anyhow::Context;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc};
+
+impl Course {
+    /// 获取课程公告. 优先使用 REST API，失败时退回到抓取课程公告页.
+    pub async fn list_announcements(&self) -> anyhow::Result<Vec<CourseAnnouncementHandle>> {
+        let data = with_cache(
+            &format!("Course::list_announcements_{}", self.meta.id),
+            self.client.cache_ttl(),
+            self._list_announcements(),
+        )
+        .await;
+
+        let data = match data {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!(
+                    "fetch announcements of {} via REST API failed, fallback to course page: {e:#}",
+                    self.meta.title()
+                );
+                return self.list_announcements_from_coursepage().await;
+            }
+        };
+
+        Ok(data
+            .into_iter()
+            .map(|content| CourseAnnouncementHandle {
+                course: self.meta.clone(),
+                content: Arc::new(content),
+            })
+            .collect())
+    }
+
+    async fn _list_announcements(&self) -> anyhow::Result<Vec<CourseContentData>> {
+        let mut url = format!(
+            "{API_BASE}/learn/api/public/v1/courses/{}/announcements?limit=100",
+            self.meta.id
+        );
+        let mut announcements = Vec::new();
+        loop {
+            let page: Page = self
+                .client
+                .api_get(&url)
+                .await
+                .context("fetch announcements")?;
+            announcements.extend(page.results);
+            match page.paging.and_then(|p| p.next_page) {
+                Some(next) => url = format!("{API_BASE}{next}"),
+                None => break,
+            }
+        }
+        log::info!(
+            "found {} announcements for course {}",
+            announcements.len(),
+            self.meta.title()
+        );
+
+        // 发布者只给出了用户 ID，需要逐个查询姓名. 学生可能没有权限查询，此时留空.
+        let mut authors = HashMap::new();
+        for a in &announcements {
+            let Some(creator) = &a.creator else {
+                continue;
+            };
+            if authors.contains_key(creator) {
+                continue;
+            }
+            let name = match self.user_name(creator).await {
+                Ok(name) => Some(name),
+                Err(e) => {
+                    log::debug!("fetch name of user {creator}: {e:#}");
+                    None
+                }
+            };
+            authors.insert(creator.clone(), name);
+        }
+
+        Ok(announcements
+            .into_iter()
+            .filter(|a| !a.draft)
+            .map(|a| {
+                // 公告的附件以链接的形式出现在正文中
+                let body = scraper::Html::parse_fragment(a.body.as_deref().unwrap_or_default());
+                let root = body.root_element();
+                let attachments = collect_file_links(root, "a[href]", &[]);
+                let embedded = collect_file_links(root, "img[src]", &attachments);
+                CourseContentData {
+                    id: a.id,
+                    title: a.title.trim().to_owned(),
+                    kind: CourseContentKind::Announcement,
+                    has_link: false,
+                    descriptions: crate::markdown::from_element(root)
+                        .split("\n\n")
+                        .map(str::trim)
+                        .filter(|p| !p.is_empty())
+                        .map(ToOwned::to_owned)
+                        .collect(),
+                    attachments,
+                    embedded,
+                    time: a.created,
+                    author: a.creator.and_then(|c| authors.get(&c).cloned()).flatten(),
+                    parent: None,
+                    path: vec![],
+                }
+            })
+            .collect())
+    }
+
+    async fn user_name(&self, user_id: &str) -> anyhow::Result<String> {
+        #[derive(Debug, Deserialize)]
+        struct User {
+            name: UserName,
+        }
+        #[derive(Debug, Deserialize)]
+        struct UserName {
+            #[serde(default)]
+            given: String,
+            #[serde(default)]
+            family: String,
+        }
+
+        let u: User = self
+            .client
+            .api_get(&format!(
+                "{API_BASE}/learn/api/public/v1/users/{user_id}?fields=name"
+            ))
+            .await?;
+        Ok(person_name(&u.name.given, &u.name.family))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    results: Vec<Announcement>,
+    paging: Option<Paging>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Paging {
+    #[serde(rename = "nextPage")]
+    next_page: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Announcement {
+    id: String,
+    title: String,
+    /// HTML 格式的正文
+    body: Option<String>,
+    /// 发布者的用户 ID
+    creator: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    /// ISO 8601 格式的创建时间
+    created: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page() {
+        let s = r#"{
+            "results": [{
+                "id": "_123_1",
+                "title": "期中考试安排",
+                "body": "<p>期中考试于<strong>第九周</strong>周三进行。</p>",
+                "creator": "_45_1",
+                "draft": false,
+                "availability": {"duration": {"type": "Permanent"}},
+                "created": "2025-04-01T02:30:00.000Z",
+                "modified": "2025-04-01T02:30:00.000Z",
+                "position": 1
+            }],
+            "paging": {"nextPage": "/learn/api/public/v1/courses/_1_1/announcements?limit=100&offset=100"}
+        }"#;
+        let page: Page = serde_json::from_str(s).unwrap();
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].creator.as_deref(), Some("_45_1"));
+        assert_eq!(
+            page.paging.and_then(|p| p.next_page).as_deref(),
+            Some("/learn/api/public/v1/courses/_1_1/announcements?limit=100&offset=100")
+        );
+
+        let last: Page = serde_json::from_str(r#"{"results": []}"#).unwrap();
+        assert!(last.paging.is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use super::{API_BASE, Blackboard};
+use crate::utils::with_cache;
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+/// 教学网限制单次查询的时间范围不超过 16 周
+const MAX_RANGE: chrono::TimeDelta = chrono::TimeDelta::weeks(16);
+
+impl Blackboard {
+    /// 获取日历中 `[since, until)` 范围内的事件，包括学校、课程和个人日程以及成绩项的截止时间.
+    pub async fn calendar_items(
+        &self,
+        since: DateTime<FixedOffset>,
+        until: DateTime<FixedOffset>,
+    ) -> anyhow::Result<Vec<CalendarItem>> {
+        with_cache(
+            &format!(
+                "Blackboard::calendar_items_{}_{}",
+                since.timestamp(),
+                until.timestamp()
+            ),
+            self.client.cache_ttl(),
+            self._calendar_items(since, until),
+        )
+        .await
+    }
+
+    async fn _calendar_items(
+        &self,
+        since: DateTime<FixedOffset>,
+        until: DateTime<FixedOffset>,
+    ) -> anyhow::Result<Vec<CalendarItem>> {
+        let mut items = Vec::new();
+        let mut start = since;
+        while start < until {
+            let end = (start + MAX_RANGE).min(until);
+            let mut url = format!(
+                "{API_BASE}/learn/api/public/v1/calendars/items?since={}&until={}",
+                utc_iso(start),
+                utc_iso(end)
+            );
+            loop {
+                let page: Page = self
+                    .client
+                    .api_get(&url)
+                    .await
+                    .context("fetch calendar items")?;
+                items.extend(page.results);
+                match page.paging.and_then(|p| p.next_page) {
+                    Some(next) => url = format!("{API_BASE}{next}"),
+                    None => break,
+                }
+            }
+            start = end;
+        }
+
+        let mut items = items
+            .into_iter()
+            .filter_map(|it| {
+                let start = DateTime::parse_from_rfc3339(&it.start).ok()?;
+                let end = it
+                    .end
+                    .as_deref()
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+                let description = it
+                    .description
+                    .as_deref()
+                    .map(|d| {
+                        crate::markdown::from_element(
+                            scraper::Html::parse_fragment(d).root_element(),
+                        )
+                    })
+                    .filter(|d| !d.is_empty());
+                Some(CalendarItem {
+                    id: it.id,
+                    kind: it.kind,
+                    calendar_id: it.calendar_id,
+                    calendar_name: it.calendar_name,
+                    title: it.title,
+                    description,
+                    location: it.location.filter(|l| !l.trim().is_empty()),
+                    start,
+                    end,
+                })
+            })
+            .collect::<Vec<_>>();
+        // 分段查询时跨越分段的事件会重复出现
+        items.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.id.cmp(&b.id)));
+        items.dedup_by(|a, b| a.id == b.id);
+        Ok(items)
+    }
+}
+
+/// 转换为 UTC 时间，避免时区中的 `+` 出现在查询参数中
+fn utc_iso(t: DateTime<FixedOffset>) -> String {
+    t.with_timezone(&chrono::Utc)
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    results: Vec<Item>,
+    paging: Option<Paging>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Paging {
+    #[serde(rename = "nextPage")]
+    next_page: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    id: String,
+    /// `Course`, `GradebookColumn`, `Institution`, `OfficeHours` 或 `Personal`
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(rename = "calendarId", default)]
+    calendar_id: String,
+    #[serde(rename = "calendarName", default)]
+    calendar_name: String,
+    title: String,
+    /// HTML 格式的说明
+    description: Option<String>,
+    location: Option<String>,
+    /// ISO 8601 格式的开始时间
+    start: String,
+    end: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CalendarItem {
+    id: String,
+    kind: String,
+    calendar_id: String,
+    calendar_name: String,
+    title: String,
+    description: Option<String>,
+    location: Option<String>,
+    start: DateTime<FixedOffset>,
+    end: Option<DateTime<FixedOffset>>,
+}
+
+impl CalendarItem {
+    /// 事件类型的中文名称
+    pub fn kind(&self) -> &str {
+        match self.kind.as_str() {
+            "Course" => "课程",
+            "GradebookColumn" => "截止",
+            "Institution" => "学校",
+            "OfficeHours" => "答疑",
+            "Personal" => "个人",
+            s => s,
+        }
+    }
+    /// 是否为成绩项 (作业、测验等) 的截止时间
+    pub fn is_due(&self) -> bool {
+        self.kind == "GradebookColumn"
+    }
+    /// 日历 ID，课程事件为课程 ID
+    pub fn calendar_id(&self) -> &str {
+        &self.calendar_id
+    }
+    /// 日历名称，课程事件为课程名称
+    pub fn calendar_name(&self) -> &str {
+        &self.calendar_name
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    /// Markdown 格式的说明
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+    pub fn start(&self) -> DateTime<FixedOffset> {
+        self.start
+    }
+    pub fn end(&self) -> Option<DateTime<FixedOffset>> {
+        self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page() {
+        let s = r##"{"results": [{
+            "id": "_blackboard.platform.gradebook2.GradableItem-_12_1",
+            "type": "GradebookColumn",
+            "calendarId": "_1_1",
+            "calendarName": "数据结构与算法(2024-2025学年第2学期)",
+            "title": "作业3",
+            "start": "2025-04-01T15:59:00.000Z",
+            "end": "2025-04-01T15:59:00.000Z",
+            "color": "#0000FF"
+        }]}"##;
+        let page: Page = serde_json::from_str(s).unwrap();
+        assert!(page.paging.is_none());
+        let it = &page.results[0];
+        assert_eq!(it.kind, "GradebookColumn");
+        assert_eq!(it.calendar_id, "_1_1");
+        assert_eq!(
+            DateTime::parse_from_rfc3339(&it.start).unwrap(),
+            DateTime::parse_from_rfc3339("2025-04-01T23:59:00+08:00").unwrap()
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use super::{API_BASE, Blackboard, CourseMeta};
+use anyhow::Context;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::LazyLock};
+
+type DateTime = chrono::DateTime<chrono::FixedOffset>;
+
+/// 课程所属的学期
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CourseTerm {
+    id: String,
+    name: String,
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+}
+
+impl CourseTerm {
+    /// 学期名称，如 `25-26学年第1学期`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 学期开始时间，学期没有设置起止日期时为 `None`
+    pub fn start(&self) -> Option<DateTime> {
+        self.start
+    }
+
+    /// 学期结束时间，学期没有设置起止日期时为 `None`
+    pub fn end(&self) -> Option<DateTime> {
+        self.end
+    }
+
+    /// 时间 `t` 是否位于学期的起止日期之间，学期没有设置起止日期时为 `None`
+    fn contains(&self, t: DateTime) -> Option<bool> {
+        Some(self.start? <= t && t <= self.end?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Paged<T> {
+    results: Vec<T>,
+    paging: Option<Paging>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Paging {
+    #[serde(rename = "nextPage")]
+    next_page: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Membership {
+    #[serde(rename = "courseId")]
+    course_id: String,
+    availability: Option<Availability>,
+    course: Option<RestCourse>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestCourse {
+    id: String,
+    /// 课程编号，即首页课程列表中冒号前的部分
+    #[serde(rename = "courseId")]
+    course_id: String,
+    name: String,
+    #[serde(rename = "externalId")]
+    external_id: Option<String>,
+    #[serde(rename = "termId")]
+    term_id: Option<String>,
+    availability: Option<Availability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestTerm {
+    id: String,
+    name: String,
+    availability: Option<Availability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Availability {
+    available: String,
+    duration: Option<Duration>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Duration {
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+}
+
+impl Availability {
+    /// `No` 和 `Disabled` 表示学生无法访问
+    fn is_available(&self) -> bool {
+        !matches!(self.available.as_str(), "No" | "Disabled")
+    }
+}
+
+impl Blackboard {
+    /// 通过 REST API 获取用户选修的课程及其学期
+    pub(super) async fn rest_courses(&self) -> anyhow::Result<Vec<CourseMeta>> {
+        let user_id = self.user_info_id().await?;
+
+        let mut memberships = Vec::new();
+        let mut url = Some(format!(
+            "{API_BASE}/learn/api/public/v1/users/{user_id}/courses?expand=course&limit=100"
+        ));
+        while let Some(u) = url {
+            let page: Paged<Membership> = self
+                .client
+                .api_get(&u)
+                .await
+                .context("fetch user courses")?;
+            memberships.extend(page.results);
+            url = page
+                .paging
+                .and_then(|p| p.next_page)
+                .map(|p| format!("{API_BASE}{p}"));
+        }
+
+        let mut courses = Vec::new();
+        for m in memberships {
+            if m.availability.as_ref().is_some_and(|a| !a.is_available()) {
+                continue;
+            }
+            let course = match m.course {
+                Some(c) => c,
+                // 旧版本的 API 不支持 expand，逐个获取课程详情
+                None => self
+                    .client
+                    .api_get(&format!(
+                        "{API_BASE}/learn/api/public/v3/courses/{}?fields=id,courseId,name,externalId,termId,availability",
+                        m.course_id
+                    ))
+                    .await
+                    .with_context(|| format!("fetch course {}", m.course_id))?,
+            };
+            if course
+                .availability
+                .as_ref()
+                .is_some_and(|a| !a.is_available())
+            {
+                log::debug!("skip unavailable course {}", course.name);
+                continue;
+            }
+            courses.push(course);
+        }
+
+        let mut terms = HashMap::new();
+        for id in courses.iter().filter_map(|c| c.term_id.as_deref()) {
+            if terms.contains_key(id) {
+                continue;
+            }
+            // 学期信息只用于判断是否是当前学期，获取失败时忽略
+            let term = match self.rest_term(id).await {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    log::debug!("fetch term {id}: {e:#}");
+                    None
+                }
+            };
+            terms.insert(id.to_owned(), term);
+        }
+
+        let metas = courses
+            .into_iter()
+            .filter_map(|c| {
+                let term = c.term_id.as_ref().and_then(|id| terms.get(id)?.as_ref());
+                // 课程可用性为 `Term` 时跟随学期
+                if c.availability
+                    .as_ref()
+                    .is_some_and(|a| a.available == "Term")
+                    && term.is_some_and(|(_, available)| !available)
+                {
+                    return None;
+                }
+                Some(CourseMeta {
+                    id: c.id,
+                    long_title: format!("{}: {}", c.course_id, c.name),
+                    is_current: false,
+                    term: term.map(|(t, _)| t.clone()),
+                    external_id: c.external_id.filter(|s| !s.is_empty()),
+                })
+            })
+            .collect();
+        Ok(metas)
+    }
+
+    /// 获取学期的名称和起止日期，以及学期是否可用
+    async fn rest_term(&self, id: &str) -> anyhow::Result<(CourseTerm, bool)> {
+        let t: RestTerm = self
+            .client
+            .api_get(&format!("{API_BASE}/learn/api/public/v1/terms/{id}"))
+            .await
+            .context("fetch term")?;
+        let available = t.availability.as_ref().is_none_or(|a| a.is_available());
+        let duration = t.availability.and_then(|a| a.duration);
+        Ok((
+            CourseTerm {
+                id: t.id,
+                name: t.name,
+                start: duration.as_ref().and_then(|d| d.start),
+                end: duration.and_then(|d| d.end),
+            },
+            available,
+        ))
+    }
+}
+
+/// 从学期名称或课程标题中解析学期，如 `25-26学年第1学期` 解析为 `(25, 1)`
+fn semester_key(s: &str) -> Option<(u32, u32)> {
+    static RE: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"(\d{2})-\d{2}学年第(\d)学期").unwrap());
+    let caps = RE.captures(s)?;
+    Some((caps[1].parse().ok()?, caps[2].parse().ok()?))
+}
+
+/// 判断各课程是否属于当前学期：学期设置了起止日期时看 `now` 是否在其中，
+/// 否则按学期名称 (或课程标题中的学期) 取最近的一个学期. 两者都没有时为 `None`.
+pub(super) fn term_current(courses: &[CourseMeta], now: DateTime) -> Vec<Option<bool>> {
+    let key = |c: &CourseMeta| {
+        c.term
+            .as_ref()
+            .and_then(|t| semester_key(&t.name))
+            .or_else(|| semester_key(&c.long_title))
+    };
+    let latest = courses.iter().filter_map(key).max();
+    courses
+        .iter()
+        .map(|c| {
+            c.term
+                .as_ref()
+                .and_then(|t| t.contains(now))
+                .or_else(|| Some(key(c)? == latest?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(title: &str, term: Option<CourseTerm>) -> CourseMeta {
+        CourseMeta {
+            id: String::new(),
+            long_title: title.to_owned(),
+            is_current: false,
+            term,
+            external_id: None,
+        }
+    }
+
+    #[test]
+    fn test_semester_key() {
+        assert_eq!(semester_key("25-26学年第1学期"), Some((25, 1)));
+        assert_eq!(
+            semester_key("25261-00048: 高等数学 (B) (一)(25-26学年第2学期)"),
+            Some((25, 2))
+        );
+        assert_eq!(semester_key("教学网使用指南"), None);
+    }
+
+    #[test]
+    fn test_term_current() {
+        let t = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+        let term = |name: &str, range: Option<(&str, &str)>| CourseTerm {
+            id: String::new(),
+            name: name.to_owned(),
+            start: range.map(|(s, _)| t(s)),
+            end: range.map(|(_, e)| t(e)),
+        };
+        let courses = [
+            meta("a: 数学分析(24-25学年第2学期)", None),
+            meta("b: 线性代数", Some(term("25-26学年第1学期", None))),
+            meta("c: 教学网使用指南", None),
+            meta(
+                "d: 程序设计实习",
+                Some(term(
+                    "2025 秋季",
+                    Some(("2025-09-01T00:00:00Z", "2026-01-31T00:00:00Z")),
+                )),
+            ),
+        ];
+        assert_eq!(
+            term_current(&courses, t("2025-10-01T00:00:00Z")),
+            [Some(false), Some(true), None, Some(true)]
+        );
+    }
+
+    #[test]
+    fn test_parse_memberships() {
+        let s = r#"{"results": [{"courseId": "_1_1", "availability": {"available": "Yes"},
+            "course": {"id": "_1_1", "courseId": "25261-001", "name": "线性代数",
+                       "externalId": "25261-00048-1", "termId": "_5_1", "availability": {"available": "Term"}}}],
+            "paging": {"nextPage": "/learn/api/public/v1/users/_2_1/courses?offset=100"}}"#;
+        let page: Paged<Membership> = serde_json::from_str(s).unwrap();
+        let course = page.results[0].course.as_ref().unwrap();
+        assert_eq!(course.term_id.as_deref(), Some("_5_1"));
+        assert_eq!(course.external_id.as_deref(), Some("25261-00048-1"));
+        assert!(page.paging.unwrap().next_page.is_some());
+    }
+}
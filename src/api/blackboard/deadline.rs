@@ -0,0 +1,290 @@
+//! 作业截止时间、公告发布时间等教学网上时间的解析.
+//!
+//! 教学网显示的时间均为北京时间，与运行 pku3b 的机器所在时区无关，因此统一按
+//! UTC+8 解析. 支持中文/英文界面以及 12/24 小时制的写法.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone as _};
+use std::sync::LazyLock;
+
+/// 北京时间 (Asia/Shanghai, 自 1991 年起无夏令时)
+pub fn shanghai() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).unwrap()
+}
+
+static RE_ZH: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"(\d{4})\s*年\s*(\d{1,2})\s*月\s*(\d{1,2})\s*日(?:\s*(?:星期|周).)?\s*(上午|下午|中午|晚上|凌晨)?\s*(\d{1,2})\s*[:时点]\s*(\d{2})(?:\s*分(?:\s*(\d{2})\s*秒)?|:(\d{2}))?",
+    )
+    .unwrap()
+});
+
+static RE_EN: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"(?i)\b(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+(\d{1,2}),?\s+(\d{4})(?:,|\s+at)?\s+(\d{1,2}):(\d{2})(?::(\d{2}))?\s*([ap]\.?m\.?)?",
+    )
+    .unwrap()
+});
+
+static RE_NUMERIC: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"(?i)(\d{4})[-/.](\d{1,2})[-/.](\d{1,2})\s+(\d{1,2}):(\d{2})(?::(\d{2}))?\s*([ap]\.?m\.?|上午|下午)?",
+    )
+    .unwrap()
+});
+
+/// 截止时间. 保留页面上的原始字符串，同时提供解析后的北京时间.
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    raw: String,
+    time: DateTime<FixedOffset>,
+}
+
+impl Deadline {
+    /// 从教学网页面上的文本中解析截止时间，无法识别时返回 `None`.
+    ///
+    /// 也接受 REST API 返回的 ISO 8601 时间戳.
+    pub fn parse(raw: &str) -> Option<Self> {
+        if let Ok(time) = DateTime::parse_from_rfc3339(raw.trim()) {
+            return Some(Self {
+                raw: raw.to_owned(),
+                time: time.with_timezone(&shanghai()),
+            });
+        }
+
+        let naive = parse_zh(raw)
+            .or_else(|| parse_en(raw))
+            .or_else(|| parse_numeric(raw))?;
+        let time = shanghai().from_local_datetime(&naive).single()?;
+        Some(Self {
+            raw: raw.to_owned(),
+            time,
+        })
+    }
+
+    /// 原始的截止时间字符串
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// 截止时间 (北京时间)
+    pub fn time(&self) -> DateTime<FixedOffset> {
+        self.time
+    }
+
+    /// 截止时间 (本机时区)
+    pub fn local(&self) -> DateTime<chrono::Local> {
+        self.time.with_timezone(&chrono::Local)
+    }
+
+    /// 距离截止时间还剩多久，已截止时为负
+    pub fn remaining(&self) -> chrono::TimeDelta {
+        self.time.signed_duration_since(chrono::Utc::now())
+    }
+}
+
+impl PartialEq for Deadline {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time
+    }
+}
+
+impl Eq for Deadline {}
+
+impl PartialOrd for Deadline {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Deadline {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.time().cmp(&other.time())
+    }
+}
+
+/// 将 12 小时制的时间转换为 24 小时制. `period` 为 `None` 时视为 24 小时制.
+fn to_24h(hour: u32, period: Option<&str>) -> Option<u32> {
+    let Some(period) = period else {
+        return (hour < 24).then_some(hour);
+    };
+    if hour == 0 || hour > 12 {
+        return None;
+    }
+    let period = period.to_ascii_lowercase().replace('.', "");
+    let hour = match period.as_str() {
+        "am" | "上午" | "凌晨" => hour % 12,
+        "pm" | "下午" | "晚上" => hour % 12 + 12,
+        // 中午 12 点半写作「中午12:30」，中午 1 点写作「中午1:00」
+        "中午" if hour >= 11 => hour,
+        "中午" => hour + 12,
+        _ => return None,
+    };
+    Some(hour)
+}
+
+fn build(
+    (year, month, day): (i32, u32, u32),
+    (hour, minute, second): (u32, u32, u32),
+    period: Option<&str>,
+) -> Option<NaiveDateTime> {
+    let hour = to_24h(hour, period)?;
+    Some(NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(year, month, day)?,
+        NaiveTime::from_hms_opt(hour, minute, second)?,
+    ))
+}
+
+fn parse_zh(s: &str) -> Option<NaiveDateTime> {
+    let caps = RE_ZH.captures(s)?;
+    let num = |i: usize| caps.get(i).map_or(Some(0), |m| m.as_str().parse().ok());
+    build(
+        (caps[1].parse().ok()?, num(2)?, num(3)?),
+        (
+            num(5)?,
+            num(6)?,
+            num(if caps.get(7).is_some() { 7 } else { 8 })?,
+        ),
+        caps.get(4).map(|m| m.as_str()),
+    )
+}
+
+fn parse_en(s: &str) -> Option<NaiveDateTime> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let caps = RE_EN.captures(s)?;
+    let num = |i: usize| caps.get(i).map_or(Some(0), |m| m.as_str().parse().ok());
+    let month = caps[1].to_ascii_lowercase();
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    build(
+        (caps[3].parse().ok()?, month, num(2)?),
+        (num(4)?, num(5)?, num(6)?),
+        caps.get(7).map(|m| m.as_str()),
+    )
+}
+
+fn parse_numeric(s: &str) -> Option<NaiveDateTime> {
+    let caps = RE_NUMERIC.captures(s)?;
+    let num = |i: usize| caps.get(i).map_or(Some(0), |m| m.as_str().parse().ok());
+    build(
+        (caps[1].parse().ok()?, num(2)?, num(3)?),
+        (num(4)?, num(5)?, num(6)?),
+        caps.get(7).map(|m| m.as_str()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(s: &str) -> String {
+        Deadline::parse(s)
+            .map(|d| d.time().format("%Y-%m-%d %H:%M:%S %:z").to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_deadline_zh_12h() {
+        assert_eq!(
+            parsed("到期日期 2025年3月14日 星期五 下午11:59"),
+            "2025-03-14 23:59:00 +08:00"
+        );
+        assert_eq!(
+            parsed("2025年3月14日 星期五 上午9:05"),
+            "2025-03-14 09:05:00 +08:00"
+        );
+        assert_eq!(
+            parsed("2025年3月14日 星期五 下午12:30"),
+            "2025-03-14 12:30:00 +08:00"
+        );
+        assert_eq!(
+            parsed("2025年3月14日 星期五 中午12:00"),
+            "2025-03-14 12:00:00 +08:00"
+        );
+    }
+
+    #[test]
+    fn test_deadline_zh_midnight() {
+        assert_eq!(
+            parsed("2025年3月15日 星期六 上午12:00"),
+            "2025-03-15 00:00:00 +08:00"
+        );
+        assert_eq!(
+            parsed("2025年3月15日 星期六 上午12:59"),
+            "2025-03-15 00:59:00 +08:00"
+        );
+    }
+
+    #[test]
+    fn test_deadline_zh_24h() {
+        assert_eq!(
+            parsed("2025年3月14日 星期五 23:59"),
+            "2025-03-14 23:59:00 +08:00"
+        );
+        assert_eq!(
+            parsed("2025年3月14日 0:00:30"),
+            "2025-03-14 00:00:30 +08:00"
+        );
+    }
+
+    #[test]
+    fn test_deadline_zh_hms_units() {
+        assert_eq!(
+            parsed("发布时间: 2025年3月3日 星期一 下午02时31分05秒 CST"),
+            "2025-03-03 14:31:05 +08:00"
+        );
+        assert_eq!(
+            parsed("2025年3月3日 星期一 上午9时05分"),
+            "2025-03-03 09:05:00 +08:00"
+        );
+    }
+
+    #[test]
+    fn test_deadline_en() {
+        assert_eq!(
+            parsed("Due Date: Friday, March 14, 2025 11:59 PM"),
+            "2025-03-14 23:59:00 +08:00"
+        );
+        assert_eq!(
+            parsed("Mar 15, 2025 12:00:00 AM CST"),
+            "2025-03-15 00:00:00 +08:00"
+        );
+        assert_eq!(
+            parsed("Sept. 1, 2025 at 8:00 a.m."),
+            "2025-09-01 08:00:00 +08:00"
+        );
+        assert_eq!(
+            parsed("December 31, 2025 23:59"),
+            "2025-12-31 23:59:00 +08:00"
+        );
+    }
+
+    #[test]
+    fn test_deadline_numeric() {
+        assert_eq!(parsed("2025-03-14 23:59"), "2025-03-14 23:59:00 +08:00");
+        assert_eq!(parsed("2025/3/14 11:59 PM"), "2025-03-14 23:59:00 +08:00");
+    }
+
+    #[test]
+    fn test_deadline_rfc3339() {
+        assert_eq!(
+            parsed("2025-03-14T15:59:00.000Z"),
+            "2025-03-14 23:59:00 +08:00"
+        );
+    }
+
+    #[test]
+    fn test_deadline_invalid() {
+        assert_eq!(parsed("无截止日期"), "");
+        assert_eq!(parsed("2025年2月30日 星期五 下午11:59"), "");
+        assert_eq!(parsed("2025年3月14日 星期五 下午13:00"), "");
+    }
+
+    #[test]
+    fn test_deadline_keeps_raw_and_orders_by_time() {
+        let a = Deadline::parse("2025年3月14日 星期五 下午11:59").unwrap();
+        let b = Deadline::parse("Mar 15, 2025 12:30 AM").unwrap();
+        assert_eq!(a.raw(), "2025年3月14日 星期五 下午11:59");
+        assert!(a < b);
+    }
+}
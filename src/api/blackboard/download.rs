@@ -0,0 +1,493 @@
+use super::{Client, low_level};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// 目标文件已存在时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnConflict {
+    /// 跳过，不下载
+    Skip,
+    /// 覆盖已有文件
+    #[default]
+    Overwrite,
+    /// 在文件名后加上序号，如 `讲义 (1).pdf`
+    Rename,
+}
+
+impl OnConflict {
+    /// 返回实际要写入的路径. `path` 已存在且策略为 [`OnConflict::Skip`] 时返回 `None`.
+    pub fn resolve(self, path: &Path) -> Option<PathBuf> {
+        self.resolve_unclaimed(path, |_| false)
+    }
+
+    /// 同 [`Self::resolve`]，但不使用 `claimed` 返回 `true` 的路径，这些路径总是改为加上序号
+    fn resolve_unclaimed(self, path: &Path, claimed: impl Fn(&Path) -> bool) -> Option<PathBuf> {
+        let mut candidates = std::iter::once(path.to_owned())
+            .chain((1..).map(|i| numbered(path, i)))
+            .filter(|p| !claimed(p));
+        loop {
+            let p = candidates.next()?;
+            if !p.exists() {
+                return Some(p);
+            }
+            match self {
+                Self::Skip => return None,
+                Self::Overwrite => return Some(p),
+                Self::Rename => continue,
+            }
+        }
+    }
+}
+
+/// 同一批附件已经选定的保存路径. 附件并发下载，且实际文件名在收到响应后才能确定，
+/// 因此在确定文件名时立即登记，避免两个附件写入同一个文件.
+#[derive(Debug, Default)]
+pub struct ClaimedPaths(RefCell<HashSet<PathBuf>>);
+
+impl ClaimedPaths {
+    /// 按 `on_conflict` 确定 `path` 的实际保存路径并登记，见 [`OnConflict::resolve`].
+    /// 已被同一批其他附件登记的路径会加上序号.
+    pub fn claim(&self, path: &Path, on_conflict: OnConflict) -> Option<PathBuf> {
+        let mut claimed = self.0.borrow_mut();
+        let p = on_conflict.resolve_unclaimed(path, |p| claimed.contains(p))?;
+        claimed.insert(p.clone());
+        Some(p)
+    }
+}
+
+/// `dir/name.ext` -> `dir/name (i).ext`
+pub fn numbered(path: &Path, i: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem} ({i}).{}", ext.to_string_lossy()),
+        None => format!("{stem} ({i})"),
+    };
+    path.with_file_name(name)
+}
+
+/// 对一组附件名称调用 [`sanitize_filename`]，并为重名的附件加上序号，避免同时下载时相互覆盖
+pub fn dedup_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let name = sanitize_filename(name);
+            let path = Path::new(&name);
+            let name = std::iter::once(name.clone())
+                .chain((1..).map(|i| numbered(path, i).to_string_lossy().into_owned()))
+                .find(|n| !seen.contains(n))
+                .unwrap();
+            seen.insert(name.clone());
+            name
+        })
+        .collect()
+}
+
+/// 附件的保存位置
+#[derive(Debug, Clone, Copy)]
+pub enum DownloadTarget<'a> {
+    /// 保存到确定的路径，覆盖已有文件
+    Path(&'a Path),
+    /// 保存到目录 `dir` 中. 文件名优先使用服务器在 `Content-Disposition` 中给出的名称，
+    /// 否则使用 `name`，均经过 [`sanitize_filename`] 处理.
+    Dir {
+        dir: &'a Path,
+        name: &'a str,
+        on_conflict: OnConflict,
+        /// 同时下载多个附件时共享的已登记路径
+        claimed: Option<&'a ClaimedPaths>,
+    },
+}
+
+/// Windows 上不能用作文件名的设备名
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 文件名的最大长度 (字节)，留出 `.part` 和重名序号的空间
+const MAX_NAME_LEN: usize = 200;
+
+/// 将服务器给出的名称转换为安全的文件名：替换路径分隔符和各平台不允许的字符，
+/// 去掉控制字符和首尾的空白及末尾的 `.`，避免 `..`、设备名和过长的文件名.
+pub fn sanitize_filename(name: &str) -> String {
+    let name = name
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect::<String>();
+    let mut name = name.trim().trim_end_matches(['.', ' ']).to_owned();
+
+    let stem = name.split('.').next().unwrap_or_default();
+    if name.is_empty() {
+        return "_".to_owned();
+    }
+    if RESERVED_NAMES
+        .iter()
+        .any(|r| r.eq_ignore_ascii_case(stem.trim()))
+    {
+        name.insert(0, '_');
+    }
+
+    if name.len() > MAX_NAME_LEN {
+        // 尽量保留扩展名
+        let ext = name
+            .rfind('.')
+            .filter(|&i| i > 0 && name.len() - i <= 16)
+            .map(|i| name[i..].to_owned())
+            .unwrap_or_default();
+        let mut end = MAX_NAME_LEN - ext.len();
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name = format!("{}{ext}", name[..end].trim_end());
+    }
+    name
+}
+
+/// 从 `Content-Disposition` 响应头中取出文件名，优先使用 RFC 5987 编码的 `filename*`
+fn content_disposition_filename(header: &str) -> Option<String> {
+    static RE_EXT: LazyLock<regex::Regex> = LazyLock::new(|| {
+        regex::Regex::new(r#"(?i)filename\*\s*=\s*([\w!#$&+.^`|~-]+)'[^']*'([^;\s]+)"#).unwrap()
+    });
+    static RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+        regex::Regex::new(r#"(?i)(?:^|;)\s*filename\s*=\s*(?:"((?:[^"\\]|\\.)*)"|([^;]+))"#)
+            .unwrap()
+    });
+
+    let decode = |s: &str| {
+        percent_encoding::percent_decode_str(s)
+            .decode_utf8()
+            .ok()
+            .map(|s| s.into_owned())
+    };
+
+    if let Some(cap) = RE_EXT.captures(header) {
+        // 只支持 UTF-8 编码
+        if cap[1].eq_ignore_ascii_case("utf-8")
+            && let Some(name) = decode(&cap[2])
+        {
+            return Some(name).filter(|n| !n.trim().is_empty());
+        }
+    }
+
+    let cap = RE.captures(header)?;
+    let name = match (cap.get(1), cap.get(2)) {
+        (Some(quoted), _) => quoted.as_str().replace("\\\"", "\"").replace("\\\\", "\\"),
+        (_, Some(token)) => token.as_str().trim().to_owned(),
+        _ => return None,
+    };
+    // 部分服务器会将非 ASCII 的文件名进行百分号编码
+    let name = match name.is_ascii() && name.contains('%') {
+        true => decode(&name).unwrap_or(name),
+        false => name,
+    };
+    Some(name).filter(|n| !n.trim().is_empty())
+}
+
+impl Client {
+    /// 下载附件到 `target`，下载过程中调用 `progress(已下载字节数, 总字节数)`，总字节数未知时为 0.
+    /// 返回文件的保存路径，因文件已存在而跳过时返回 `None`.
+    ///
+    /// 数据先写入 `<文件名>.part`，下载完成后再重命名. 如果 `.part` 文件已经存在
+    /// (上次下载被中断)，则通过 HTTP Range 从断点处继续下载，并用 `If-Range` 确认远程文件没有变化.
+    pub async fn course_attachment_download(
+        &self,
+        uri: &str,
+        target: DownloadTarget<'_>,
+        redir: bool,
+        progress: impl Fn(u64, u64),
+    ) -> anyhow::Result<Option<PathBuf>> {
+        use compio::io::AsyncWriteAtExt as _;
+        use futures_util::StreamExt as _;
+
+        let (fallback, on_conflict, claimed) = match target {
+            DownloadTarget::Path(p) => (p.to_owned(), OnConflict::Overwrite, None),
+            DownloadTarget::Dir {
+                dir,
+                name,
+                on_conflict,
+                claimed,
+            } => (dir.join(sanitize_filename(name)), on_conflict, claimed),
+        };
+        let part = part_path(&fallback);
+        let tag = tag_path(&part);
+        let mut offset = compio::fs::metadata(&part).await.map_or(0, |m| m.len());
+        let mut validator = match offset {
+            0 => None,
+            _ => compio::fs::read(&tag)
+                .await
+                .ok()
+                .and_then(|v| String::from_utf8(v).ok())
+                .filter(|v| !v.is_empty()),
+        };
+        // 无法确认 `.part` 文件属于远程文件的当前版本，从头下载
+        if offset > 0 && validator.is_none() {
+            log::debug!("{} has no validator, discarded", part.display());
+            offset = 0;
+        }
+        if offset == 0 && on_conflict.resolve(&fallback).is_none() {
+            log::info!("{} exists, skipped", fallback.display());
+            return Ok(None);
+        }
+
+        let res = loop {
+            log::debug!("downloading attachment from {uri} (resume from {offset})");
+            let mut res = self
+                .get_by_uri_from(uri, offset, validator.as_deref())
+                .await?;
+            // 正文中嵌入的链接可能已经是真实地址，不会再重定向
+            if redir && res.status().is_redirection() {
+                let loc = low_level::extract_redirect_url(&res)?.to_owned();
+                log::debug!("redirected to {loc}");
+                res = self
+                    .get_by_uri_from(&loc, offset, validator.as_deref())
+                    .await?;
+            }
+            // 总长度与 `.part` 文件不符，`.part` 文件已无效
+            if offset > 0
+                && res.status() == http::StatusCode::RANGE_NOT_SATISFIABLE
+                && content_range(&res).is_none_or(|(_, total)| total != Some(offset))
+            {
+                log::debug!("{} does not match remote file, restart", part.display());
+                compio::fs::remove_file(&part).await?;
+                let _ = compio::fs::remove_file(&tag).await;
+                (offset, validator) = (0, None);
+                continue;
+            }
+            break res;
+        };
+
+        let dest = match target {
+            DownloadTarget::Dir { dir, .. } => res
+                .headers()
+                .get(http::header::CONTENT_DISPOSITION)
+                .and_then(|v| content_disposition_filename(&String::from_utf8_lossy(v.as_bytes())))
+                .map_or(fallback, |name| dir.join(sanitize_filename(&name))),
+            DownloadTarget::Path(_) => fallback,
+        };
+        let resolved = match claimed {
+            Some(claimed) => claimed.claim(&dest, on_conflict),
+            None => on_conflict.resolve(&dest),
+        };
+        let Some(dest) = resolved else {
+            log::info!("{} exists, skipped", dest.display());
+            return Ok(None);
+        };
+
+        let range = content_range(&res);
+        let offset = match res.status() {
+            http::StatusCode::PARTIAL_CONTENT => {
+                anyhow::ensure!(
+                    range.is_some_and(|(start, _)| start == Some(offset)),
+                    "unexpected content range {range:?}, expect start {offset}"
+                );
+                offset
+            }
+            // 服务器不支持 Range 或远程文件已变化，从头下载
+            s if s.is_success() => {
+                match response_validator(&res) {
+                    Some(v) => compio::fs::write(&tag, v).await.0?,
+                    None => {
+                        let _ = compio::fs::remove_file(&tag).await;
+                    }
+                }
+                0
+            }
+            // `.part` 文件已经是完整的
+            http::StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+                progress(offset, offset);
+                compio::fs::rename(&part, &dest).await?;
+                let _ = compio::fs::remove_file(&tag).await;
+                return Ok(Some(dest));
+            }
+            s => anyhow::bail!("status not success: {s}"),
+        };
+        let total = range
+            .and_then(|(_, total)| total)
+            .or_else(|| res.content_length().map(|n| n + offset))
+            .unwrap_or(0);
+
+        let mut file = compio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(offset == 0)
+            .open(&part)
+            .await?;
+        let mut pos = offset;
+        progress(pos, total);
+        let mut stream = std::pin::pin!(res.bytes_stream());
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            let n = chunk.len() as u64;
+            compio::buf::buf_try!(@try file.write_all_at(chunk, pos).await);
+            pos += n;
+            progress(pos, total);
+        }
+        file.close().await?;
+        anyhow::ensure!(
+            total == 0 || pos == total,
+            "connection closed after {pos} of {total} bytes"
+        );
+
+        compio::fs::rename(&part, &dest).await?;
+        let _ = compio::fs::remove_file(&tag).await;
+        Ok(Some(dest))
+    }
+}
+
+/// 未下载完成的文件：`<dest>.part`
+fn part_path(dest: &Path) -> PathBuf {
+    let mut p = dest.as_os_str().to_owned();
+    p.push(".part");
+    p.into()
+}
+
+/// 记录 `.part` 文件对应的远程版本 (ETag 或 Last-Modified)：`<dest>.part.tag`
+fn tag_path(part: &Path) -> PathBuf {
+    let mut p = part.as_os_str().to_owned();
+    p.push(".tag");
+    p.into()
+}
+
+/// 响应中可用于 `If-Range` 的校验值：强 ETag，否则为 Last-Modified
+fn response_validator(res: &cyper::Response) -> Option<String> {
+    let get = |name| {
+        res.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned)
+    };
+    get(http::header::ETAG)
+        .filter(|v| !v.starts_with("W/"))
+        .or_else(|| get(http::header::LAST_MODIFIED))
+}
+
+fn content_range(res: &cyper::Response) -> Option<(Option<u64>, Option<u64>)> {
+    res.headers()
+        .get(http::header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_content_range)
+}
+
+/// 解析 `Content-Range` 响应头 (如 `bytes 100-199/200`、`bytes */200`)，返回起始位置和总长度
+fn parse_content_range(s: &str) -> Option<(Option<u64>, Option<u64>)> {
+    let (range, total) = s.strip_prefix("bytes ")?.split_once('/')?;
+    let start = match range {
+        "*" => None,
+        r => Some(r.split_once('-')?.0.parse().ok()?),
+    };
+    Some((start, total.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/200"),
+            Some((Some(100), Some(200)))
+        );
+        assert_eq!(parse_content_range("bytes */200"), Some((None, Some(200))));
+        assert_eq!(parse_content_range("bytes 0-99/*"), Some((Some(0), None)));
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("作业1.pdf"), "作业1.pdf");
+        assert_eq!(sanitize_filename("../../etc/passwd"), ".._.._etc_passwd");
+        assert_eq!(sanitize_filename(".."), "_");
+        assert_eq!(sanitize_filename("  a\tb\u{7}?.txt. "), "ab_.txt");
+        assert_eq!(sanitize_filename("con.txt"), "_con.txt");
+        assert_eq!(sanitize_filename(r"C:\a|b"), "C__a_b");
+
+        let long = format!("{}.pdf", "讲".repeat(100));
+        let s = sanitize_filename(&long);
+        assert!(s.len() <= MAX_NAME_LEN && s.ends_with("讲.pdf"));
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        let f = content_disposition_filename;
+        assert_eq!(
+            f(r#"attachment; filename="report.pdf""#).as_deref(),
+            Some("report.pdf")
+        );
+        assert_eq!(
+            f("inline; filename*=UTF-8''%E8%AE%B2%E4%B9%89%201.pdf").as_deref(),
+            Some("讲义 1.pdf")
+        );
+        assert_eq!(
+            f(r#"attachment; filename="a.pdf"; filename*=utf-8''b.pdf"#).as_deref(),
+            Some("b.pdf")
+        );
+        assert_eq!(
+            f("attachment; filename=%E8%AE%B2%E4%B9%89.pdf").as_deref(),
+            Some("讲义.pdf")
+        );
+        assert_eq!(f(r#"attachment; filename="""#), None);
+        assert_eq!(f("inline"), None);
+    }
+
+    #[test]
+    fn test_dedup_names() {
+        assert_eq!(
+            dedup_names(["a.pdf", "b.pdf", "a.pdf", "a/b", "a.pdf"]),
+            ["a.pdf", "b.pdf", "a (1).pdf", "a_b", "a (2).pdf"]
+        );
+    }
+
+    #[test]
+    fn test_claimed_paths() {
+        let dir = std::env::temp_dir().join(format!("pku3b-claim-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.pdf"), b"").unwrap();
+
+        // 两个附件的服务器文件名都是 `a.pdf`
+        let claimed = ClaimedPaths::default();
+        let a = dir.join("a.pdf");
+        assert_eq!(claimed.claim(&a, OnConflict::Overwrite), Some(a.clone()));
+        assert_eq!(
+            claimed.claim(&a, OnConflict::Overwrite),
+            Some(dir.join("a (1).pdf"))
+        );
+
+        let claimed = ClaimedPaths::default();
+        assert_eq!(
+            claimed.claim(&a, OnConflict::Rename),
+            Some(dir.join("a (1).pdf"))
+        );
+        assert_eq!(
+            claimed.claim(&a, OnConflict::Rename),
+            Some(dir.join("a (2).pdf"))
+        );
+
+        let claimed = ClaimedPaths::default();
+        assert_eq!(claimed.claim(&a, OnConflict::Skip), None);
+        let b = dir.join("b.pdf");
+        assert_eq!(claimed.claim(&b, OnConflict::Skip), Some(b.clone()));
+        assert_eq!(
+            claimed.claim(&b, OnConflict::Skip),
+            Some(dir.join("b (1).pdf"))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_on_conflict_numbered() {
+        assert_eq!(
+            numbered(Path::new("dir/讲义.pdf"), 2),
+            Path::new("dir/讲义 (2).pdf")
+        );
+        assert_eq!(numbered(Path::new("README"), 1), Path::new("README (1)"));
+    }
+}
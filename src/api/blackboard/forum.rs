@@ -0,0 +1,573 @@
+use super::{Client, Course, CourseMeta};
+use crate::qs;
+use crate::utils::{invalidate_cache, with_cache};
+use anyhow::Context as _;
+use scraper::{ElementRef, Html, Selector};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+    str::FromStr,
+    sync::{Arc, LazyLock},
+};
+
+impl Course {
+    pub async fn get_forums(&self) -> anyhow::Result<Vec<CourseForumHandle>> {
+        log::info!("fetching forums for course {}", self.meta.title());
+
+        let forums = with_cache(
+            &forums_cache_key(&self.meta.id),
+            self.client.cache_ttl(),
+            self._get_forums(),
+        )
+        .await?;
+
+        Ok(forums
+            .into_iter()
+            .map(|meta| CourseForumHandle {
+                client: self.client.clone(),
+                meta: meta.into(),
+                course: self.meta.clone(),
+            })
+            .collect())
+    }
+
+    async fn _get_forums(&self) -> anyhow::Result<Vec<CourseForumMeta>> {
+        let dom = self
+            .client
+            .bb_course_discussion_board(&self.meta.id)
+            .await?;
+        Ok(parse_forums(&dom))
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CourseForumMeta {
+    conf_id: String,
+    forum_id: String,
+    title: String,
+    description: String,
+    total: Option<u32>,
+    unread: Option<u32>,
+}
+
+impl CourseForumMeta {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    /// 帖子总数
+    pub fn total(&self) -> Option<u32> {
+        self.total
+    }
+    /// 未读帖子数
+    pub fn unread(&self) -> Option<u32> {
+        self.unread
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CourseForumHandle {
+    client: Client,
+    meta: Arc<CourseForumMeta>,
+    course: Arc<CourseMeta>,
+}
+
+impl CourseForumHandle {
+    pub fn meta(&self) -> &CourseForumMeta {
+        &self.meta
+    }
+
+    pub async fn get_threads(&self) -> anyhow::Result<Vec<CourseThreadHandle>> {
+        let threads = with_cache(
+            &threads_cache_key(&self.meta.forum_id, &self.course.id),
+            self.client.cache_ttl(),
+            self._get_threads(),
+        )
+        .await?;
+
+        Ok(threads
+            .into_iter()
+            .map(|meta| CourseThreadHandle {
+                client: self.client.clone(),
+                course: self.course.clone(),
+                forum: self.meta.clone(),
+                meta: meta.into(),
+            })
+            .collect())
+    }
+
+    async fn _get_threads(&self) -> anyhow::Result<Vec<CourseThreadMeta>> {
+        let dom = self
+            .client
+            .bb_course_forum(&self.course.id, &self.meta.conf_id, &self.meta.forum_id)
+            .await?;
+        Ok(parse_threads(&dom))
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CourseThreadMeta {
+    message_id: String,
+    title: String,
+    author: String,
+    time: String,
+    unread: Option<u32>,
+    total: Option<u32>,
+}
+
+impl CourseThreadMeta {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+    /// 未读帖子数
+    pub fn unread(&self) -> Option<u32> {
+        self.unread
+    }
+    /// 帖子总数 (包括主题帖)
+    pub fn total(&self) -> Option<u32> {
+        self.total
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CourseThreadHandle {
+    client: Client,
+    course: Arc<CourseMeta>,
+    forum: Arc<CourseForumMeta>,
+    meta: Arc<CourseThreadMeta>,
+}
+
+impl CourseThreadHandle {
+    /// Thread identifier computed from hash.
+    pub fn id(&self) -> String {
+        let mut hasher = std::hash::DefaultHasher::new();
+        self.course.id.hash(&mut hasher);
+        self.forum.forum_id.hash(&mut hasher);
+        self.meta.message_id.hash(&mut hasher);
+        let x = hasher.finish();
+        format!("{x:x}")
+    }
+
+    pub fn meta(&self) -> &CourseThreadMeta {
+        &self.meta
+    }
+
+    pub fn forum(&self) -> &CourseForumMeta {
+        &self.forum
+    }
+
+    /// 获取主题帖及其下所有回复. 不使用缓存，打开后教学网会将其标记为已读.
+    pub async fn get_posts(&self) -> anyhow::Result<Vec<ForumPost>> {
+        let dom = self
+            .client
+            .bb_course_thread(
+                &self.course.id,
+                &self.forum.conf_id,
+                &self.forum.forum_id,
+                &self.meta.message_id,
+            )
+            .await?;
+        let posts = parse_posts(&dom);
+        anyhow::ensure!(!posts.is_empty(), "no post found in thread");
+        self.invalidate_lists().await;
+        Ok(posts)
+    }
+
+    /// 未读数和帖子数已经改变，删除论坛列表和主题帖列表的缓存
+    async fn invalidate_lists(&self) {
+        invalidate_cache::<Vec<CourseForumMeta>>(&forums_cache_key(&self.course.id)).await;
+        invalidate_cache::<Vec<CourseThreadMeta>>(&threads_cache_key(
+            &self.forum.forum_id,
+            &self.course.id,
+        ))
+        .await;
+    }
+
+    /// 回复主题帖，`text` 作为纯文本提交. 提交后重新获取主题帖，确认出现了新的帖子.
+    pub async fn reply(&self, text: &str) -> anyhow::Result<()> {
+        let before = self.get_posts().await?;
+        let dom = self
+            .client
+            .bb_course_thread_replypage(
+                &self.course.id,
+                &self.forum.conf_id,
+                &self.forum.forum_id,
+                &self.meta.message_id,
+            )
+            .await?;
+
+        let mut fields = parse_reply_form(&dom).context("reply form not found")?;
+        log::trace!("fields: {fields:#?}");
+
+        fields
+            .entry("title".to_owned())
+            .or_insert_with(|| format!("RE: {}", self.meta.title));
+        fields.insert("messagetext".to_owned(), text.to_owned());
+        // 纯文本格式
+        fields.insert("messagetext_f".to_owned(), "P".to_owned());
+
+        let body = fields
+            .iter()
+            .fold(crate::multipart::MultipartBuilder::new(), |body, (k, v)| {
+                body.add_field(k, v.as_bytes())
+            });
+
+        let res = self.client.bb_course_thread_replydata(body).await?;
+        let st = res.status();
+        if !st.is_success() && !st.is_redirection() {
+            log::debug!("response: {}", res.text().await?);
+            anyhow::bail!("invalid status {st}");
+        }
+
+        let after = self.get_posts().await.context("fetch posts after reply")?;
+        anyhow::ensure!(
+            has_new_post(&before, &after),
+            "reply not found in thread after posting"
+        );
+        Ok(())
+    }
+}
+
+/// 回复页面中包含 nonce 的表单的字段及其默认值
+fn parse_reply_form(dom: &Html) -> Option<HashMap<String, String>> {
+    let form = dom.select(&Selector::parse("form").unwrap()).find(|f| {
+        f.select(
+            &Selector::parse(r#"input[name="blackboard.platform.security.NonceUtil.nonce"]"#)
+                .unwrap(),
+        )
+        .next()
+        .is_some()
+    })?;
+
+    let (input_sel, textarea_sel) = (
+        Selector::parse("input[name]").unwrap(),
+        Selector::parse("textarea[name]").unwrap(),
+    );
+    let inputs = form
+        .select(&input_sel)
+        .filter(|input| {
+            !matches!(
+                input.value().attr("type"),
+                Some("submit" | "button" | "file" | "checkbox" | "radio")
+            )
+        })
+        .map(|input| {
+            let name = input.value().attr("name").unwrap().to_owned();
+            let value = input.value().attr("value").unwrap_or_default().to_owned();
+            (name, value)
+        });
+    let textareas = form.select(&textarea_sel).map(|t| {
+        let name = t.value().attr("name").unwrap().to_owned();
+        (name, t.text().collect::<String>())
+    });
+    Some(inputs.chain(textareas).collect())
+}
+
+/// `after` 中是否有 `before` 中没有的帖子. 帖子没有 ID 时比较帖子数.
+fn has_new_post(before: &[ForumPost], after: &[ForumPost]) -> bool {
+    let ids = before
+        .iter()
+        .filter_map(|p| p.id.as_deref())
+        .collect::<HashSet<_>>();
+    if after.iter().all(|p| p.id.is_none()) {
+        return after.len() > before.len();
+    }
+    after
+        .iter()
+        .filter_map(|p| p.id.as_deref())
+        .any(|id| !ids.contains(id))
+}
+
+fn forums_cache_key(course_id: &str) -> String {
+    format!("Course::get_forums_{course_id}")
+}
+
+fn threads_cache_key(forum_id: &str, course_id: &str) -> String {
+    format!("CourseForumHandle::get_threads_{forum_id}_{course_id}")
+}
+
+/// 帖子内容，正文为若干段 Markdown
+#[derive(Debug)]
+pub struct ForumPost {
+    /// 帖子 ID，如 `_123_1`，页面中没有时为 `None`
+    id: Option<String>,
+    title: String,
+    author: String,
+    time: String,
+    body: Vec<String>,
+}
+
+impl ForumPost {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+    pub fn body(&self) -> &[String] {
+        &self.body
+    }
+}
+
+fn text_of(el: ElementRef<'_>) -> String {
+    el.text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn enclosing_row(el: ElementRef<'_>) -> Option<ElementRef<'_>> {
+    el.ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "tr")
+}
+
+/// 表格行中各单元格的文字
+fn row_cells(tr: ElementRef<'_>) -> Vec<String> {
+    tr.child_elements()
+        .filter(|c| matches!(c.value().name(), "td" | "th"))
+        .map(text_of)
+        .collect()
+}
+
+/// 讨论板页面中每个论坛一行：论坛、描述、帖子总数、未读帖子、回复我的帖子、参与者
+fn parse_forums(dom: &Html) -> Vec<CourseForumMeta> {
+    let mut seen = HashSet::new();
+    let mut forums = Vec::new();
+    for a in dom.select(&Selector::parse(r#"a[href*="action=list_threads"]"#).unwrap()) {
+        let title = text_of(a);
+        let Some(q) = a
+            .value()
+            .attr("href")
+            .and_then(|h| qs::Query::from_str(h).ok())
+        else {
+            continue;
+        };
+        let (Some(conf_id), Some(forum_id)) = (q.get("conf_id"), q.get("forum_id")) else {
+            continue;
+        };
+        if title.is_empty() || !seen.insert(forum_id.to_owned()) {
+            continue;
+        }
+
+        let cells = enclosing_row(a).map(row_cells).unwrap_or_default();
+        let numbers = cells
+            .iter()
+            .filter_map(|s| s.parse::<u32>().ok())
+            .collect::<Vec<_>>();
+        let description = cells
+            .iter()
+            .find(|s| !s.is_empty() && !s.contains(&title) && s.parse::<u32>().is_err())
+            .cloned()
+            .unwrap_or_default();
+
+        forums.push(CourseForumMeta {
+            conf_id: conf_id.to_owned(),
+            forum_id: forum_id.to_owned(),
+            title,
+            description,
+            total: numbers.first().copied(),
+            unread: numbers.get(1).copied(),
+        });
+    }
+    forums
+}
+
+/// 论坛页面中每个主题帖一行：日期、主题、作者、状态、未读帖子、未读的回复我的帖子、帖子总数
+fn parse_threads(dom: &Html) -> Vec<CourseThreadMeta> {
+    let mut seen = HashSet::new();
+    let mut threads = Vec::new();
+    for a in dom.select(&Selector::parse(r#"a[href*="action=list_messages"]"#).unwrap()) {
+        let title = text_of(a);
+        let Some(message_id) = a
+            .value()
+            .attr("href")
+            .and_then(|h| qs::Query::from_str(h).ok())
+            .and_then(|q| q.get("message_id").map(ToOwned::to_owned))
+        else {
+            continue;
+        };
+        if title.is_empty() || !seen.insert(message_id.clone()) {
+            continue;
+        }
+
+        let cells = enclosing_row(a).map(row_cells).unwrap_or_default();
+        let pos = cells.iter().position(|s| s.contains(&title));
+        let is_text = |s: &&String| !s.is_empty() && s.parse::<u32>().is_err();
+        let (time, author) = match pos {
+            Some(pos) => (
+                cells[..pos].iter().rfind(is_text).cloned(),
+                cells[pos + 1..].iter().find(is_text).cloned(),
+            ),
+            None => (None, None),
+        };
+        let numbers = cells
+            .iter()
+            .filter_map(|s| s.parse::<u32>().ok())
+            .collect::<Vec<_>>();
+
+        threads.push(CourseThreadMeta {
+            message_id,
+            title,
+            author: author.unwrap_or_default(),
+            time: time.unwrap_or_default(),
+            unread: numbers.first().copied(),
+            total: numbers.last().copied(),
+        });
+    }
+    threads
+}
+
+/// 帖子的 ID：`div.dbThreadMessage` 或其中元素的 `id` 属性中形如 `_123_1` 的部分
+fn post_id(msg: ElementRef<'_>) -> Option<String> {
+    static RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"_\d+_\d+").unwrap());
+    std::iter::once(msg)
+        .chain(msg.descendants().filter_map(ElementRef::wrap))
+        .filter_map(|e| e.value().attr("id"))
+        .find_map(|id| RE.find(id))
+        .map(|m| m.as_str().to_owned())
+}
+
+/// 主题帖页面中的每个帖子位于 `div.dbThreadMessage` 中
+fn parse_posts(dom: &Html) -> Vec<ForumPost> {
+    let first_text = |el: ElementRef<'_>, sel: &str| {
+        el.select(&Selector::parse(sel).unwrap())
+            .next()
+            .map(text_of)
+            .unwrap_or_default()
+    };
+
+    dom.select(&Selector::parse("div.dbThreadMessage").unwrap())
+        .map(|msg| {
+            let body = msg
+                .select(&Selector::parse("div.vtbegenerated").unwrap())
+                .next()
+                .map(crate::markdown::from_element)
+                .unwrap_or_default();
+            ForumPost {
+                id: post_id(msg),
+                title: first_text(msg, ".dbThreadTitle, h3"),
+                author: first_text(msg, ".profileCardAvatarThumb, .authorName"),
+                time: first_text(msg, ".dateStamp, .timestamp"),
+                body: body
+                    .split("\n\n")
+                    .filter(|p| !p.trim().is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forums() {
+        let html = r#"<table><tbody id="listContainer_databody">
+            <tr>
+              <th><a href="/webapps/discussionboard/do/forum?action=list_threads&course_id=_1_1&nav=discussion_board_entry&conf_id=_2_1&forum_id=_3_1">答疑区</a></th>
+              <td><div class="vtbegenerated">课程相关问题在这里提问</div></td>
+              <td>12</td><td>3</td><td>0</td><td>8</td>
+            </tr>
+            <tr>
+              <th><a href="/webapps/discussionboard/do/forum?action=list_threads&course_id=_1_1&nav=discussion_board_entry&conf_id=_2_1&forum_id=_4_1">闲聊</a></th>
+              <td></td><td>0</td><td>0</td><td>0</td><td>0</td>
+            </tr>
+        </tbody></table>"#;
+        let forums = parse_forums(&Html::parse_document(html));
+        assert_eq!(forums.len(), 2);
+        assert_eq!(forums[0].title, "答疑区");
+        assert_eq!(forums[0].forum_id, "_3_1");
+        assert_eq!(forums[0].conf_id, "_2_1");
+        assert_eq!(forums[0].description, "课程相关问题在这里提问");
+        assert_eq!((forums[0].total, forums[0].unread), (Some(12), Some(3)));
+        assert_eq!(forums[1].description, "");
+    }
+
+    #[test]
+    fn test_parse_threads() {
+        let html = r#"<table><tbody id="listContainer_databody">
+            <tr>
+              <td><input type="checkbox"></td>
+              <td>24-3-5 下午3:20</td>
+              <th><a href="/webapps/discussionboard/do/message?action=list_messages&course_id=_1_1&forum_id=_3_1&nav=discussion_board_entry&message_id=_9_1">第二次作业第 3 题</a></th>
+              <td>张三</td><td>已发布</td><td>2</td><td>0</td><td>5</td>
+            </tr>
+        </tbody></table>"#;
+        let threads = parse_threads(&Html::parse_document(html));
+        assert_eq!(threads.len(), 1);
+        let t = &threads[0];
+        assert_eq!(t.message_id, "_9_1");
+        assert_eq!(t.title, "第二次作业第 3 题");
+        assert_eq!(t.time, "24-3-5 下午3:20");
+        assert_eq!(t.author, "张三");
+        assert_eq!((t.unread, t.total), (Some(2), Some(5)));
+    }
+
+    #[test]
+    fn test_parse_posts() {
+        let html = r#"<div id="dbThreadContent">
+            <div class="dbThreadMessage" id="_9_1">
+              <h3 class="dbThreadTitle">第二次作业第 3 题</h3>
+              <span class="profileCardAvatarThumb">张三</span>
+              <span class="dateStamp">2024年3月5日 星期二 下午03时20分</span>
+              <div class="vtbegenerated"><p>请问第 3 题的 <b>n</b> 是否可以为 0？</p><p>谢谢</p></div>
+            </div>
+            <div class="dbThreadMessage">
+              <div id="message_10_1_body"></div>
+              <h3 class="dbThreadTitle">RE: 第二次作业第 3 题</h3>
+              <span class="profileCardAvatarThumb">助教</span>
+              <span class="dateStamp">2024年3月5日 星期二 下午05时00分</span>
+              <div class="vtbegenerated"><p>可以。</p></div>
+            </div>
+        </div>"#;
+        let posts = parse_posts(&Html::parse_document(html));
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0].author, "张三");
+        assert_eq!(
+            posts[0].body,
+            ["请问第 3 题的 **n** 是否可以为 0？", "谢谢"]
+        );
+        assert_eq!(posts[1].title, "RE: 第二次作业第 3 题");
+        assert_eq!(posts[1].time, "2024年3月5日 星期二 下午05时00分");
+        assert_eq!(posts[0].id.as_deref(), Some("_9_1"));
+        assert_eq!(posts[1].id.as_deref(), Some("_10_1"));
+
+        // 回复内容与已有帖子相同时也只认新的帖子 ID
+        let (before, after) = posts.split_at(1);
+        assert!(has_new_post(before, &posts));
+        assert!(!has_new_post(&posts, &posts));
+        assert!(!has_new_post(&posts, after));
+    }
+
+    #[test]
+    fn test_parse_reply_form() {
+        let html = r#"<form id="search"><input name="q" value="x"></form>
+            <form id="postForm">
+              <input type="hidden" name="blackboard.platform.security.NonceUtil.nonce" value="n1">
+              <input type="text" name="title" value="RE: 第二次作业">
+              <input type="submit" name="bottom_Submit" value="提交">
+              <textarea name="messagetext_w">默认内容</textarea>
+            </form>"#;
+        let fields = parse_reply_form(&Html::parse_document(html)).unwrap();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields["blackboard.platform.security.NonceUtil.nonce"], "n1");
+        assert_eq!(fields["messagetext_w"], "默认内容");
+        assert!(!fields.contains_key("q"));
+        assert!(parse_reply_form(&Html::parse_document("<form></form>")).is_none());
+    }
+}
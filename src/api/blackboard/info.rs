@@ -0,0 +1,376 @@
+use super::{API_BASE, Course, page::page_markdown, person_name};
+use crate::api::low_level::blackboard::ApiStatusError;
+use crate::utils::with_partial_cache;
+use anyhow::Context;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+impl Course {
+    /// 获取课程的教学人员、上课时间和课程简介.
+    ///
+    /// 教学人员优先从 REST API 的课程成员列表获取，学生没有权限时退回到课程菜单中的
+    /// "教师信息" 页面；上课时间和简介来自课程详情以及 "课程信息"、"教学大纲" 等页面.
+    /// 有页面获取失败时结果不会被缓存.
+    pub async fn info(&self) -> anyhow::Result<CourseInfo> {
+        log::info!("fetching course info for {}", self.meta.title());
+
+        with_partial_cache(
+            &format!("Course::info_{}", self.meta.id),
+            self.client.cache_ttl(),
+            self._info(),
+        )
+        .await
+    }
+
+    async fn _info(&self) -> anyhow::Result<(CourseInfo, bool)> {
+        let mut staff = match self.memberships().await {
+            Ok(r) => r,
+            Err(e) if is_forbidden(&e) => {
+                log::debug!("fetch memberships of {}: {e:#}", self.meta.title());
+                Vec::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut description = match self.rest_description().await {
+            Ok(r) => r,
+            Err(e) if is_forbidden(&e) => {
+                log::debug!("fetch description of {}: {e:#}", self.meta.title());
+                Vec::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut meeting_times = Vec::new();
+        let mut pages = self
+            .entries()
+            .iter()
+            .filter(|(name, _)| INFO_ENTRIES.iter().any(|k| name.contains(k)))
+            .collect::<Vec<_>>();
+        pages.sort();
+        let mut complete = true;
+        for (name, uri) in pages {
+            let dom = match self.client.page_by_uri(uri).await {
+                Ok(dom) => dom,
+                Err(e) => {
+                    log::warn!("fetch page {name} of {}: {e:#}", self.meta.title());
+                    complete = false;
+                    continue;
+                }
+            };
+
+            if name.contains("教师") {
+                for s in parse_staff_page(&dom) {
+                    // REST API 给出的信息更准确，只补充其中没有的人员
+                    if !staff.iter().any(|t| t.name == s.name) {
+                        staff.push(s);
+                    }
+                }
+                continue;
+            }
+
+            let blocks = page_blocks(&dom);
+            for line in blocks.iter().flat_map(|b| b.lines()) {
+                if is_meeting_time(line) && !meeting_times.iter().any(|t| t == line.trim()) {
+                    meeting_times.push(line.trim().to_owned());
+                }
+            }
+            if description.is_empty() && name.contains("课程信息") {
+                description = blocks;
+            }
+        }
+
+        let info = CourseInfo {
+            staff,
+            meeting_times,
+            description,
+        };
+        Ok((info, complete))
+    }
+
+    async fn memberships(&self) -> anyhow::Result<Vec<CourseStaff>> {
+        #[derive(Debug, Deserialize)]
+        struct Page {
+            results: Vec<Membership>,
+            paging: Option<Paging>,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Paging {
+            #[serde(rename = "nextPage")]
+            next_page: Option<String>,
+        }
+
+        let mut url = format!(
+            "{API_BASE}/learn/api/public/v1/courses/{}/users?expand=user&limit=200",
+            self.meta.id
+        );
+        let mut members = Vec::new();
+        loop {
+            let page: Page = self
+                .client
+                .api_get(&url)
+                .await
+                .context("fetch course memberships")?;
+            members.extend(page.results);
+            match page.paging.and_then(|p| p.next_page) {
+                Some(next) => url = format!("{API_BASE}{next}"),
+                None => break,
+            }
+        }
+
+        let mut staff = members
+            .into_iter()
+            .filter_map(|m| {
+                let role = role_name(&m.course_role_id)?;
+                let user = m.user?;
+                Some(CourseStaff {
+                    name: person_name(&user.name.given, &user.name.family),
+                    role: Some(role.to_owned()),
+                    email: user.contact.and_then(|c| c.email).filter(|e| !e.is_empty()),
+                    details: Vec::new(),
+                })
+            })
+            .collect::<Vec<_>>();
+        // 教师排在助教前面
+        staff.sort_by_key(|s| ROLES.iter().position(|(_, r)| s.role.as_deref() == Some(r)));
+        Ok(staff)
+    }
+
+    async fn rest_description(&self) -> anyhow::Result<Vec<String>> {
+        #[derive(Debug, Deserialize)]
+        struct Detail {
+            description: Option<String>,
+        }
+
+        let d: Detail = self
+            .client
+            .api_get(&format!(
+                "{API_BASE}/learn/api/public/v3/courses/{}?fields=description",
+                self.meta.id
+            ))
+            .await
+            .context("fetch course description")?;
+        let html = Html::parse_fragment(d.description.as_deref().unwrap_or_default());
+        Ok(markdown_blocks(&crate::markdown::from_element(
+            html.root_element(),
+        )))
+    }
+}
+
+/// 学生没有权限访问 REST API 时返回 403
+fn is_forbidden(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<ApiStatusError>()
+        .is_some_and(|e| e.0 == http::StatusCode::FORBIDDEN)
+}
+
+/// 课程菜单中可能包含课程信息的入口
+const INFO_ENTRIES: &[&str] = &["教师", "课程信息", "课程介绍", "教学大纲"];
+
+/// 课程角色 ID 及其显示名称，学生等其他角色不显示
+const ROLES: &[(&str, &str)] = &[
+    ("Instructor", "教师"),
+    ("TeachingAssistant", "助教"),
+    ("Grader", "评分者"),
+    ("CourseBuilder", "课程建设者"),
+];
+
+fn role_name(role_id: &str) -> Option<&'static str> {
+    ROLES.iter().find(|(id, _)| *id == role_id).map(|(_, r)| *r)
+}
+
+fn markdown_blocks(md: &str) -> Vec<String> {
+    md.split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// 页面正文的各个 Markdown 段落
+fn page_blocks(dom: &Html) -> Vec<String> {
+    markdown_blocks(&page_markdown(dom))
+}
+
+fn is_meeting_time(line: &str) -> bool {
+    static RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+        regex::Regex::new(r"(上课|授课|课程)(时间|地点)|时间地点|上课教室").unwrap()
+    });
+    let line = line.trim();
+    line.chars().count() <= 100 && RE.is_match(line)
+}
+
+/// 解析 "教师信息" 页面：每一项为一位教师或助教
+fn parse_staff_page(dom: &Html) -> Vec<CourseStaff> {
+    let item_sel = Selector::parse("#content_listContainer > li").unwrap();
+    let name_sel = Selector::parse("h3").unwrap();
+    let mail_sel = Selector::parse(r#"a[href^="mailto:"]"#).unwrap();
+    let details_sel = Selector::parse("div.details, div.vtbegenerated").unwrap();
+
+    dom.select(&item_sel)
+        .filter_map(|li| {
+            let name = li
+                .select(&name_sel)
+                .next()?
+                .text()
+                .collect::<String>()
+                .trim()
+                .to_owned();
+            if name.is_empty() {
+                return None;
+            }
+            let email = li
+                .select(&mail_sel)
+                .next()
+                .and_then(|a| a.value().attr("href"))
+                .map(|h| h.trim_start_matches("mailto:").trim().to_owned());
+            let details = li
+                .select(&details_sel)
+                .next()
+                .map(|d| {
+                    crate::markdown::from_element(d)
+                        .lines()
+                        .map(str::trim)
+                        .filter(|l| !l.is_empty())
+                        .map(ToOwned::to_owned)
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(CourseStaff {
+                name,
+                role: None,
+                email,
+                details,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct Membership {
+    #[serde(rename = "courseRoleId")]
+    course_role_id: String,
+    user: Option<User>,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    name: UserName,
+    contact: Option<Contact>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserName {
+    #[serde(default)]
+    given: String,
+    #[serde(default)]
+    family: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Contact {
+    email: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CourseInfo {
+    staff: Vec<CourseStaff>,
+    meeting_times: Vec<String>,
+    description: Vec<String>,
+}
+
+impl CourseInfo {
+    /// 教师、助教等教学人员
+    pub fn staff(&self) -> &[CourseStaff] {
+        &self.staff
+    }
+    /// 上课时间和地点
+    pub fn meeting_times(&self) -> &[String] {
+        &self.meeting_times
+    }
+    /// 课程简介，每项为一段 Markdown
+    pub fn description(&self) -> &[String] {
+        &self.description
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CourseStaff {
+    name: String,
+    role: Option<String>,
+    email: Option<String>,
+    details: Vec<String>,
+}
+
+impl CourseStaff {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// 课程角色 (教师、助教等)，从教师信息页面获取时未知
+    pub fn role(&self) -> Option<&str> {
+        self.role.as_deref()
+    }
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+    /// 教师信息页面中的其他说明 (办公室、答疑时间等)
+    pub fn details(&self) -> &[String] {
+        &self.details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_staff_page() {
+        let html = r#"<ul id="content_listContainer">
+            <li class="clearfix read"><div class="item"><h3><span>张老师</span></h3></div>
+                <div class="details"><div class="vtbegenerated">
+                    <p>电子邮件: <a href="mailto:zhang@pku.edu.cn">zhang@pku.edu.cn</a></p>
+                    <p>办公室: 理科一号楼 1234</p>
+                </div></div></li>
+            <li class="clearfix read"><div class="item"><h3><span>李助教</span></h3></div></li>
+        </ul>"#;
+        let staff = parse_staff_page(&Html::parse_document(html));
+        assert_eq!(staff.len(), 2);
+        assert_eq!(staff[0].name(), "张老师");
+        assert_eq!(staff[0].email(), Some("zhang@pku.edu.cn"));
+        assert_eq!(
+            staff[0].details().last().unwrap(),
+            "办公室: 理科一号楼 1234"
+        );
+        assert_eq!(staff[1].email(), None);
+    }
+
+    #[test]
+    fn test_parse_memberships() {
+        let s = r#"{"results": [
+            {"userId": "_1_1", "courseRoleId": "Instructor",
+             "user": {"name": {"given": "三", "family": "张"}, "contact": {"email": "zs@pku.edu.cn"}}},
+            {"userId": "_2_1", "courseRoleId": "Student",
+             "user": {"name": {"given": "四", "family": "李"}}}
+        ]}"#;
+        #[derive(Deserialize)]
+        struct Page {
+            results: Vec<Membership>,
+        }
+        let page: Page = serde_json::from_str(s).unwrap();
+        let roles = page
+            .results
+            .iter()
+            .map(|m| role_name(&m.course_role_id))
+            .collect::<Vec<_>>();
+        assert_eq!(roles, [Some("教师"), None]);
+        let user = page.results[0].user.as_ref().unwrap();
+        assert_eq!(person_name(&user.name.given, &user.name.family), "张三");
+    }
+
+    #[test]
+    fn test_is_meeting_time() {
+        assert!(is_meeting_time("上课时间：周二 3-4 节，周四 1-2 节"));
+        assert!(is_meeting_time("上课地点: 二教 101"));
+        assert!(!is_meeting_time("期末考试时间另行通知"));
+    }
+}
@@ -0,0 +1,100 @@
+use super::{Course, low_level};
+use crate::utils::with_cache;
+use scraper::{Html, Selector};
+
+/// 打开菜单项时最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 5;
+
+impl Course {
+    /// 按名称查找课程菜单项：优先完全匹配，其次匹配子串
+    pub fn find_entry(&self, name: &str) -> Option<&(String, String)> {
+        let name = name.trim();
+        self.entries
+            .iter()
+            .find(|(n, _)| n.trim() == name)
+            .or_else(|| self.entries.iter().find(|(n, _)| n.contains(name)))
+    }
+
+    /// 获取课程菜单项 (或任意教学网页面) 的正文，转换为 Markdown
+    pub async fn entry_page(&self, uri: &str) -> anyhow::Result<String> {
+        log::info!("fetching page {uri} of {}", self.meta.title());
+
+        with_cache(
+            &format!("Course::entry_page_{}_{uri}", self.meta.id),
+            self.client.cache_ttl(),
+            self._entry_page(uri),
+        )
+        .await
+    }
+
+    async fn _entry_page(&self, uri: &str) -> anyhow::Result<String> {
+        let mut res = self.client.get_by_uri(uri).await?;
+        // 部分菜单项 (如外部工具) 会经过若干次跳转
+        for _ in 0..MAX_REDIRECTS {
+            if !res.status().is_redirection() {
+                break;
+            }
+            let loc = low_level::extract_redirect_url(&res)?.to_owned();
+            log::debug!("redirected to {loc}");
+            res = self.client.get_by_uri(&loc).await?;
+        }
+        anyhow::ensure!(
+            res.status().is_success(),
+            "status not success: {}",
+            res.status()
+        );
+
+        let dom = Html::parse_document(&res.text().await?);
+        Ok(page_markdown(&dom))
+    }
+}
+
+/// 将页面的主体部分转换为 Markdown. 内容列表页只取列表，其余页面依次尝试正文区域和整个页面.
+pub(super) fn page_markdown(dom: &Html) -> String {
+    let item_sel = Selector::parse("#content_listContainer > li").unwrap();
+    let items = dom
+        .select(&item_sel)
+        .map(crate::markdown::from_element)
+        .filter(|md| !md.is_empty())
+        .collect::<Vec<_>>();
+    if !items.is_empty() {
+        return items.join("\n\n");
+    }
+
+    const CANDIDATES: &[&str] = &["#contentPanel", "#content", "body"];
+    for sel in CANDIDATES {
+        let sel = Selector::parse(sel).unwrap();
+        if let Some(el) = dom.select(&sel).next() {
+            let md = crate::markdown::from_element(el);
+            if !md.is_empty() {
+                return md;
+            }
+        }
+    }
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_markdown() {
+        let html = r#"<html><body><div id="navigationPane">菜单</div>
+            <div id="content"><div id="pageTitleDiv"><h1>教学大纲</h1></div>
+            <div id="contentPanel"><p>第一周：<strong>绪论</strong></p></div></div>
+            </body></html>"#;
+        assert_eq!(
+            page_markdown(&Html::parse_document(html)),
+            "第一周：**绪论**"
+        );
+
+        let html = r#"<html><body><ul id="content_listContainer">
+            <li><h3>讲义</h3><div class="details"><p>见附件</p></div></li>
+            </ul></body></html>"#;
+        assert_eq!(
+            page_markdown(&Html::parse_document(html)),
+            "### 讲义\n\n见附件"
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use super::{Client, Course, CourseContentData, CourseMeta, Deadline, GradebookColumn};
+use crate::utils::with_partial_cache;
+use anyhow::Context;
+use scraper::Selector;
+use serde::Deserialize;
+use std::{
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+#[derive(Debug, Clone)]
+pub struct CourseQuizHandle {
+    pub(super) client: Client,
+    pub(super) course: Arc<CourseMeta>,
+    pub(super) content: Arc<CourseContentData>,
+}
+
+impl CourseQuizHandle {
+    pub fn id(&self) -> String {
+        let mut hasher = std::hash::DefaultHasher::new();
+        self.course.id.hash(&mut hasher);
+        self.content.id.hash(&mut hasher);
+        let x = hasher.finish();
+        format!("{x:x}")
+    }
+
+    /// `gradebook` 为所在课程的成绩列，见 [`Course::quiz_gradebook`]
+    pub async fn get(&self, gradebook: &QuizGradebook) -> anyhow::Result<CourseQuiz> {
+        let data = with_partial_cache(
+            &format!(
+                "CourseQuizHandle::_get_{}_{}",
+                self.content.id, self.course.id
+            ),
+            self.client.cache_ttl(),
+            self._get(gradebook),
+        )
+        .await?;
+
+        Ok(CourseQuiz {
+            content: self.content.clone(),
+            data,
+        })
+    }
+
+    /// 测试的信息分散在多个地方：开始页面上有计时和尝试次数的说明，REST API 中有
+    /// 可用时间、成绩列 (截止时间、允许尝试次数、满分) 以及尝试记录. 均尽力获取，
+    /// 单个来源失败不影响其他信息，但此时返回的结果不完整，不会被缓存.
+    async fn _get(&self, gradebook: &QuizGradebook) -> anyhow::Result<(CourseQuizData, bool)> {
+        let mut data = CourseQuizData::default();
+        let mut complete = true;
+
+        match self
+            .client
+            .bb_course_quiz_launchpage(&self.course.id, &self.content.id)
+            .await
+        {
+            Ok(dom) => data.fill_from_launchpage(&dom),
+            Err(e) => {
+                log::warn!("fetch quiz launch page of {}: {e:#}", self.content.title);
+                complete = false;
+            }
+        }
+
+        match self.availability().await {
+            Ok(Some(r)) => {
+                data.available_from = r.start;
+                data.available_until = r.end;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!("fetch quiz availability of {}: {e:#}", self.content.title);
+                complete = false;
+            }
+        }
+
+        match &gradebook.0 {
+            Some(columns) => {
+                if let Err(e) = self.fill_from_gradebook(&mut data, columns).await {
+                    log::warn!("fetch quiz gradebook of {}: {e:#}", self.content.title);
+                    complete = false;
+                }
+            }
+            None => complete = false,
+        }
+
+        Ok((data, complete))
+    }
+
+    async fn availability(&self) -> anyhow::Result<Option<AdaptiveRelease>> {
+        #[derive(Debug, Deserialize)]
+        struct Content {
+            availability: Option<ContentAvailability>,
+        }
+        #[derive(Debug, Deserialize)]
+        struct ContentAvailability {
+            #[serde(rename = "adaptiveRelease")]
+            adaptive_release: Option<AdaptiveRelease>,
+        }
+
+        let val: Content = self
+            .client
+            .api_get(&format!(
+                "https://course.pku.edu.cn/learn/api/public/v1/courses/{}/contents/{}",
+                self.course.id, self.content.id
+            ))
+            .await
+            .context("fetch content")?;
+        Ok(val.availability.and_then(|a| a.adaptive_release))
+    }
+
+    async fn fill_from_gradebook(
+        &self,
+        data: &mut CourseQuizData,
+        columns: &[GradebookColumn],
+    ) -> anyhow::Result<()> {
+        let Some(col) = columns
+            .iter()
+            .find(|c| c.content_id.as_deref() == Some(self.content.id.as_str()))
+        else {
+            return Ok(());
+        };
+
+        data.possible = col.score.as_ref().map(|s| s.possible);
+        if let Some(grading) = &col.grading {
+            if grading.due.is_some() {
+                data.due = grading.due.clone();
+            }
+            if grading.attempts_allowed.is_some() {
+                data.attempts_allowed = grading.attempts_allowed;
+            }
+        }
+
+        let attempts = self
+            .client
+            .gradebook_attempts(&self.course.id, &col.id)
+            .await?
+            .into_iter()
+            .filter(|a| a.is_attempted())
+            .collect::<Vec<_>>();
+        data.attempts_used = Some(attempts.len() as u32);
+
+        let grade = self
+            .client
+            .gradedata(&self.course.id, &col.id)
+            .await?
+            .into_iter()
+            .next()
+            .and_then(|g| g.display_grade)
+            .and_then(|d| d.score);
+        data.score = grade.or_else(|| {
+            attempts
+                .iter()
+                .filter_map(|a| a.score)
+                .max_by(|a, b| a.total_cmp(b))
+        });
+
+        Ok(())
+    }
+}
+
+/// 课程成绩中心的成绩列，同一课程的所有测试共用，获取失败时为 `None`
+#[derive(Debug)]
+pub struct QuizGradebook(Option<Vec<GradebookColumn>>);
+
+impl Course {
+    /// 获取课程的成绩列，用于补充测试的截止时间、尝试次数和成绩
+    pub async fn quiz_gradebook(&self) -> QuizGradebook {
+        match self.client.gradebook_columns(&self.meta.id).await {
+            Ok(columns) => QuizGradebook(Some(columns)),
+            Err(e) => {
+                log::warn!("fetch gradebook of {}: {e:#}", self.meta.title());
+                QuizGradebook(None)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, serde::Serialize)]
+struct AdaptiveRelease {
+    start: Option<String>,
+    end: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, serde::Serialize)]
+struct CourseQuizData {
+    available_from: Option<String>,
+    available_until: Option<String>,
+    due: Option<String>,
+    time_limit: Option<String>,
+    /// 允许的尝试次数，0 表示不限
+    attempts_allowed: Option<u32>,
+    attempts_used: Option<u32>,
+    score: Option<f64>,
+    possible: Option<f64>,
+}
+
+impl CourseQuizData {
+    /// 开始页面上的说明以 `div.label` + `div.field` 的形式给出
+    fn fill_from_launchpage(&mut self, dom: &scraper::Html) {
+        for label in dom.select(&Selector::parse("div.label").unwrap()) {
+            let Some(field) = label.next_siblings().find_map(scraper::ElementRef::wrap) else {
+                continue;
+            };
+            let label = label.text().collect::<String>();
+            let label = label.trim();
+            let value = field
+                .text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            if label.contains("计时") || label.contains("Timer") || label.contains("Timed") {
+                self.time_limit = Some(value);
+            } else if label.contains("多次尝试") || label.contains("Multiple Attempts") {
+                self.attempts_allowed = parse_attempts_allowed(&value);
+            } else if label.contains("到期日期") || label.contains("Due Date") {
+                self.due = Some(value);
+            }
+        }
+    }
+}
+
+/// 解析「此测试允许 3 次尝试」「此测试仅允许尝试一次」「此测试允许无限次尝试」等说明
+fn parse_attempts_allowed(s: &str) -> Option<u32> {
+    let lower = s.to_lowercase();
+    if lower.contains("无限") || lower.contains("unlimited") {
+        return Some(0);
+    }
+    if let Some(n) = s
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|s| !s.is_empty())
+    {
+        return n.parse().ok();
+    }
+    if lower.contains("一次") || lower.contains("once") || lower.contains("single") {
+        return Some(1);
+    }
+    None
+}
+
+#[derive(Debug, Clone)]
+pub struct CourseQuiz {
+    content: Arc<CourseContentData>,
+    data: CourseQuizData,
+}
+
+impl CourseQuiz {
+    pub fn title(&self) -> &str {
+        &self.content.title
+    }
+
+    pub fn descriptions(&self) -> &[String] {
+        &self.content.descriptions
+    }
+
+    pub fn deadline(&self) -> Option<Deadline> {
+        Deadline::parse(self.data.due.as_deref()?)
+    }
+
+    /// 开放时间
+    pub fn available_from(&self) -> Option<Deadline> {
+        Deadline::parse(self.data.available_from.as_deref()?)
+    }
+
+    /// 关闭时间
+    pub fn available_until(&self) -> Option<Deadline> {
+        Deadline::parse(self.data.available_until.as_deref()?)
+    }
+
+    /// 时间限制的说明文字
+    pub fn time_limit(&self) -> Option<&str> {
+        self.data.time_limit.as_deref()
+    }
+
+    /// 允许的尝试次数，`Some(0)` 表示不限次数
+    pub fn attempts_allowed(&self) -> Option<u32> {
+        self.data.attempts_allowed
+    }
+
+    pub fn attempts_used(&self) -> Option<u32> {
+        self.data.attempts_used
+    }
+
+    /// 得分与满分
+    pub fn score(&self) -> Option<(f64, Option<f64>)> {
+        self.data.score.map(|s| (s, self.data.possible))
+    }
+
+    /// 是否已经作答过
+    pub fn is_attempted(&self) -> bool {
+        self.data.attempts_used.is_some_and(|n| n > 0) || self.data.score.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_attempts_allowed() {
+        assert_eq!(parse_attempts_allowed("此测试允许 3 次尝试。"), Some(3));
+        assert_eq!(parse_attempts_allowed("此测试仅允许尝试一次。"), Some(1));
+        assert_eq!(parse_attempts_allowed("此测试允许无限次尝试。"), Some(0));
+        assert_eq!(
+            parse_attempts_allowed("This test allows 2 attempts."),
+            Some(2)
+        );
+        assert_eq!(
+            parse_attempts_allowed("This test can only be taken once."),
+            Some(1)
+        );
+        assert_eq!(parse_attempts_allowed("--"), None);
+    }
+
+    #[test]
+    fn test_fill_from_launchpage() {
+        let html = r#"<ul>
+            <li><div class="label">说明</div><div class="field">请独立完成</div></li>
+            <li><div class="label">多次尝试</div><div class="field">此测试允许 2 次尝试。</div></li>
+            <li><div class="label">计时</div><div class="field">此测试时间限制为 1 小时。 </div></li>
+            <li><div class="label">到期日期</div><div class="field">2025年3月14日 星期五 下午11:59</div></li>
+        </ul>"#;
+        let mut data = CourseQuizData::default();
+        data.fill_from_launchpage(&scraper::Html::parse_fragment(html));
+        assert_eq!(data.attempts_allowed, Some(2));
+        assert_eq!(
+            data.time_limit.as_deref(),
+            Some("此测试时间限制为 1 小时。")
+        );
+        assert_eq!(data.due.as_deref(), Some("2025年3月14日 星期五 下午11:59"));
+    }
+}
@@ -85,8 +85,48 @@ impl LowLevelClient {
         Ok(res)
     }
 
+    /// 同 [`Self::get_by_uri`]，`offset` 大于 0 时通过 HTTP Range 请求从 `offset` 开始的内容.
+    /// 给出 `if_range` (ETag 或 Last-Modified) 时，远程文件已变化的情况下服务器返回完整内容.
+    pub async fn get_by_uri_from(
+        &self,
+        uri: &str,
+        offset: u64,
+        if_range: Option<&str>,
+    ) -> anyhow::Result<cyper::Response> {
+        if offset == 0 {
+            return self.get_by_uri(uri).await;
+        }
+        let url = convert_uri(uri)?;
+        log::trace!("GET {url} (from byte {offset}, if-range {if_range:?})");
+        let mut req = self
+            .http_client
+            .get(url)
+            .context("create request failed")?
+            .header(http::header::RANGE, format!("bytes={offset}-"))
+            .context("set range header")?;
+        if let Some(v) = if_range {
+            req = req
+                .header(http::header::IF_RANGE, v)
+                .context("set if-range header")?;
+        }
+        let res = req.send().await?;
+        Ok(res)
+    }
+
+    /// 利用 [`convert_uri`] 将 uri 自动补全，然后发送 HEAD 请求.
+    pub async fn head_by_uri(&self, uri: &str) -> anyhow::Result<cyper::Response> {
+        let url = convert_uri(uri)?;
+        log::trace!("HEAD {url}");
+        let res = self
+            .http_client
+            .head(url)
+            .context("create request failed")?
+            .send()
+            .await?;
+        Ok(res)
+    }
+
     /// 利用 [`convert_uri`] 将 uri 自动补全，然后发送请求, 返回页面 HTML
-    #[allow(unused)]
     pub async fn page_by_uri(&self, uri: &str) -> anyhow::Result<Html> {
         let res = self.get_by_uri(uri).await?;
 
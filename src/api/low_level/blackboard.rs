@@ -10,8 +10,14 @@ pub const BB_CONTENT_FILE: &str =
     "https://course.pku.edu.cn/webapps/blackboard/execute/content/file";
 pub const COURSE_INFO: &str = "https://course.pku.edu.cn/webapps/blackboard/execute/announcement";
 pub const UPLOAD_ASSIGNMENT: &str = "https://course.pku.edu.cn/webapps/assignment/uploadAssignment";
+pub const LAUNCH_ASSESSMENT: &str =
+    "https://course.pku.edu.cn/webapps/assessment/take/launchAssessment.jsp";
 pub const LIST_CONTENT: &str =
     "https://course.pku.edu.cn/webapps/blackboard/content/listContent.jsp";
+pub const DISCUSSION_BOARD: &str =
+    "https://course.pku.edu.cn/webapps/discussionboard/do/conference";
+pub const DISCUSSION_FORUM: &str = "https://course.pku.edu.cn/webapps/discussionboard/do/forum";
+pub const DISCUSSION_MESSAGE: &str = "https://course.pku.edu.cn/webapps/discussionboard/do/message";
 pub const VIDEO_LIST: &str =
     "https://course.pku.edu.cn/webapps/bb-streammedia-hqy-BBLEARN/videoList.action";
 pub const VIDEO_SUB_INFO: &str =
@@ -28,6 +34,18 @@ impl std::fmt::Display for BlackboardUnautherizedError {
     }
 }
 
+/// REST API 返回了非成功的状态码
+#[derive(Debug)]
+pub struct ApiStatusError(pub http::StatusCode);
+
+impl std::error::Error for ApiStatusError {}
+
+impl std::fmt::Display for ApiStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "API request failed: {}", self.0)
+    }
+}
+
 impl LowLevelClient {
     pub async fn bb_login_require_otp(&self, username: &str) -> anyhow::Result<bool> {
         let data = self.iaaa_is_mobile_authen("blackboard", username).await?;
@@ -191,6 +209,30 @@ impl LowLevelClient {
         Ok(dom)
     }
 
+    /// 根据 content_id 和 course_id 获取测试的开始页面（包含计时、尝试次数等说明）.
+    pub async fn bb_course_quiz_launchpage(
+        &self,
+        course_id: &str,
+        content_id: &str,
+    ) -> anyhow::Result<Html> {
+        let res = self
+            .http_client
+            .get(LAUNCH_ASSESSMENT)?
+            .query(&[
+                ("course_id", course_id),
+                ("content_id", content_id),
+                ("mode", "view"),
+            ])?
+            .send()
+            .await?;
+
+        anyhow::ensure!(res.status().is_success(), "status not success");
+
+        let rbody = res.text().await?;
+        let dom = scraper::Html::parse_document(&rbody);
+        Ok(dom)
+    }
+
     /// 向 [`UPLOAD_ASSIGNMENT`] 发送提交作业的请求
     pub async fn bb_course_assignment_uploaddata(
         &self,
@@ -218,6 +260,139 @@ impl LowLevelClient {
         Ok(res)
     }
 
+    /// 根据 course_id 获取讨论板的论坛列表页面.
+    pub async fn bb_course_discussion_board(&self, course_id: &str) -> anyhow::Result<Html> {
+        let res = self
+            .http_client
+            .get(DISCUSSION_BOARD)?
+            .query(&[
+                ("action", "list_forums"),
+                ("course_id", course_id),
+                ("nav", "discussion_board_entry"),
+            ])?
+            .send()
+            .await?;
+
+        anyhow::ensure!(res.status().is_success(), "status not success");
+
+        let rbody = res.text().await?;
+        let dom = scraper::Html::parse_document(&rbody);
+        Ok(dom)
+    }
+
+    /// 获取论坛的主题帖列表页面.
+    pub async fn bb_course_forum(
+        &self,
+        course_id: &str,
+        conf_id: &str,
+        forum_id: &str,
+    ) -> anyhow::Result<Html> {
+        let res = self
+            .http_client
+            .get(DISCUSSION_FORUM)?
+            .query(&[
+                ("action", "list_threads"),
+                ("course_id", course_id),
+                ("conf_id", conf_id),
+                ("forum_id", forum_id),
+                ("nav", "discussion_board_entry"),
+                ("forumDisplay", "list"),
+                ("numResults", "1000"),
+            ])?
+            .send()
+            .await?;
+
+        anyhow::ensure!(res.status().is_success(), "status not success");
+
+        let rbody = res.text().await?;
+        let dom = scraper::Html::parse_document(&rbody);
+        Ok(dom)
+    }
+
+    /// 获取主题帖页面 (包含其下所有回复). 打开该页面后教学网会将帖子标记为已读.
+    pub async fn bb_course_thread(
+        &self,
+        course_id: &str,
+        conf_id: &str,
+        forum_id: &str,
+        message_id: &str,
+    ) -> anyhow::Result<Html> {
+        let res = self
+            .http_client
+            .get(DISCUSSION_MESSAGE)?
+            .query(&[
+                ("action", "list_messages"),
+                ("course_id", course_id),
+                ("conf_id", conf_id),
+                ("forum_id", forum_id),
+                ("message_id", message_id),
+                ("nav", "discussion_board_entry"),
+            ])?
+            .send()
+            .await?;
+
+        anyhow::ensure!(res.status().is_success(), "status not success");
+
+        let rbody = res.text().await?;
+        let dom = scraper::Html::parse_document(&rbody);
+        Ok(dom)
+    }
+
+    /// 获取回复帖子的编辑页面 (包含提交回复所需的表单字段).
+    pub async fn bb_course_thread_replypage(
+        &self,
+        course_id: &str,
+        conf_id: &str,
+        forum_id: &str,
+        message_id: &str,
+    ) -> anyhow::Result<Html> {
+        let res = self
+            .http_client
+            .get(DISCUSSION_MESSAGE)?
+            .query(&[
+                ("action", "reply"),
+                ("do", "reply"),
+                ("type", "thread"),
+                ("course_id", course_id),
+                ("conf_id", conf_id),
+                ("forum_id", forum_id),
+                ("message_id", message_id),
+                ("nav", "discussion_board_entry"),
+            ])?
+            .send()
+            .await?;
+
+        anyhow::ensure!(res.status().is_success(), "status not success");
+
+        let rbody = res.text().await?;
+        let dom = scraper::Html::parse_document(&rbody);
+        Ok(dom)
+    }
+
+    /// 向 [`DISCUSSION_MESSAGE`] 发送回复帖子的请求
+    pub async fn bb_course_thread_replydata(
+        &self,
+        body: multipart::MultipartBuilder<'_>,
+    ) -> anyhow::Result<cyper::Response> {
+        let boundary = body.boundary().to_owned();
+        let body = body.build().context("build multipart form body")?;
+
+        let res = self
+            .http_client
+            .post(DISCUSSION_MESSAGE)?
+            .header("origin", "https://course.pku.edu.cn")?
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )?
+            .query(&[("action", "save"), ("do", "reply")])?
+            .body(body)
+            .send()
+            .await?;
+
+        Ok(res)
+    }
+
     /// 根据 course_id 获取回放列表页面内容.
     pub async fn bb_course_video_list(&self, course_id: &str) -> anyhow::Result<Html> {
         let res = self
@@ -288,8 +463,11 @@ impl LowLevelClient {
 
         let body = res.text().await?;
         let re = regex::Regex::new(r#"document.location = '(.*?)';"#).unwrap();
-        let caps = re.captures(&body).unwrap();
-        let loc = caps.get(1).unwrap().as_str();
+        let loc = re
+            .captures(&body)
+            .and_then(|caps| caps.get(1))
+            .context("file location not found")?
+            .as_str();
         log::debug!("redirected to {loc}");
 
         let res = self.get_by_uri(loc).await?;
@@ -304,11 +482,9 @@ impl LowLevelClient {
     pub async fn api_get<T: serde::de::DeserializeOwned>(&self, url: &str) -> anyhow::Result<T> {
         let res = self.http_client.get(url)?.send().await?;
 
-        anyhow::ensure!(
-            res.status().is_success(),
-            "API request failed: {}",
-            res.status()
-        );
+        if !res.status().is_success() {
+            anyhow::bail!(ApiStatusError(res.status()));
+        }
 
         let rbody = res.text().await?;
         let data: T = serde_json::from_str(&rbody)?;
@@ -10,7 +10,6 @@ pub mod thesis_lib;
 
 use anyhow::Context;
 pub use builder::ClientBuilder;
-use chrono::TimeZone;
 use cyper::IntoUrl;
 use itertools::Itertools;
 use scraper::Selector;
@@ -22,7 +21,7 @@ use std::{
 
 use crate::{
     multipart,
-    utils::{with_cache, with_cache_bytes},
+    utils::{with_cache, with_cache_bytes, with_partial_cache},
 };
 
 struct ClientInner {
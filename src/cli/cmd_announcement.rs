@@ -1,15 +1,75 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use anyhow::Context;
 
 use super::*;
 
+/// 公告已读记录文件名，位于数据目录下
+const READ_STATE: &str = "announcements-read.json";
+
+/// 本地的公告已读记录，以 [`CourseAnnouncementHandle::key`] 为键
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct ReadState {
+    read: BTreeMap<String, chrono::DateTime<chrono::Local>>,
+}
+
+impl ReadState {
+    async fn load() -> anyhow::Result<Self> {
+        let path = utils::data_dir().join(READ_STATE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let buf = fs::read(&path).await?;
+        serde_json::from_slice(&buf).with_context(|| format!("parse {}", path.display()))
+    }
+
+    async fn save(&self) -> anyhow::Result<()> {
+        let dir = utils::data_dir();
+        fs::create_dir_all(&dir).await?;
+        let buf = serde_json::to_vec_pretty(self)?;
+        buf_try!(@try fs::write(dir.join(READ_STATE), buf).await);
+        Ok(())
+    }
+
+    fn is_read(&self, a: &CourseAnnouncementHandle) -> bool {
+        self.read.contains_key(&a.key())
+    }
+
+    /// 标记为已读，返回此前是否未读
+    fn mark_read(&mut self, a: &CourseAnnouncementHandle) -> bool {
+        if self.is_read(a) {
+            return false;
+        }
+        self.read.insert(a.key(), chrono::Local::now());
+        true
+    }
+
+    /// 旧版本以显示用的短 ID 为键，将其转换为稳定的键，返回是否有改动
+    fn migrate<'a>(
+        &mut self,
+        anns: impl IntoIterator<Item = &'a CourseAnnouncementHandle>,
+    ) -> bool {
+        let mut changed = false;
+        for a in anns {
+            if let Some(t) = self.read.remove(&a.id()) {
+                self.read.entry(a.key()).or_insert(t);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
 #[derive(clap::Args)]
 pub struct CommandAnnouncement {
     /// 强制刷新
     #[arg(short, long, default_value = "false")]
     force: bool,
 
+    /// 只处理指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, global = true)]
+    course: Option<String>,
+
     #[command(subcommand)]
     command: AnnouncementCommands,
 
@@ -26,8 +86,17 @@ enum AnnouncementCommands {
         /// 显示所有学期的课程公告
         #[arg(long, default_value = "false")]
         all_term: bool,
+        /// 只显示未读公告
+        #[arg(short, long, default_value = "false")]
+        unread: bool,
+        /// 只显示此时间之后发布的公告，如 `3d` (3 天内)、`12h`、`2w`、`2025-03-01`
+        #[arg(long)]
+        since: Option<TimeBound>,
+        /// 只显示此时间之前发布的公告，格式同 `--since`，日期包括当天
+        #[arg(long)]
+        until: Option<TimeBound>,
     },
-    /// 按 ID 查看公告详情
+    /// 按 ID 查看公告详情 (同时标记为已读)
     Show {
         /// 公告 ID（可通过 `pku3b announcement ls` 查看）
         id: String,
@@ -35,15 +104,66 @@ enum AnnouncementCommands {
         #[arg(long, default_value = "false")]
         all_term: bool,
     },
+    /// 下载公告附件
+    #[command(visible_alias("down"))]
+    Download(DownloadOptions),
+    /// 将公告标记为已读
+    MarkRead {
+        /// 公告 ID（可通过 `pku3b announcement ls` 查看）
+        #[arg(required_unless_present = "all")]
+        ids: Vec<String>,
+        /// 将所有公告标记为已读
+        #[arg(long, default_value = "false")]
+        all: bool,
+        /// 与 `--all` 一起使用时包括所有学期的课程公告
+        #[arg(long, default_value = "false")]
+        all_term: bool,
+    },
+}
+
+#[derive(clap::Args)]
+struct DownloadOptions {
+    /// 公告 ID（可通过 `pku3b announcement ls` 查看）
+    id: String,
+    /// 文件下载目录 (支持相对路径)
+    #[arg(short = 'o', long, default_value = ".")]
+    outdir: std::path::PathBuf,
+    /// 在所有学期的课程公告范围中查找
+    #[arg(long, default_value = "false")]
+    all_term: bool,
+    /// 目标文件已存在时的处理方式
+    #[arg(long, value_enum, default_value = "overwrite")]
+    on_conflict: OnConflict,
+    /// 同时下载的附件数
+    #[arg(short, long, default_value = "4")]
+    jobs: usize,
 }
 
 pub async fn run(cmd: CommandAnnouncement, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    let course = cmd.course.as_deref();
     match cmd.command {
-        AnnouncementCommands::List { all_term } => {
-            list(ctx, cmd.force, !all_term, cmd.otp_code).await?
+        AnnouncementCommands::List {
+            all_term,
+            unread,
+            since,
+            until,
+        } => {
+            let filter = ListFilter {
+                only_unread: unread,
+                since: since.map(|t| t.start()),
+                until: until.map(|t| t.end()),
+                course: cmd.course.clone(),
+            };
+            list(ctx, cmd.force, !all_term, filter, cmd.otp_code).await?
         }
         AnnouncementCommands::Show { id, all_term } => {
-            show(ctx, cmd.force, !all_term, &id, cmd.otp_code).await?
+            show(ctx, cmd.force, !all_term, course, &id, cmd.otp_code).await?
+        }
+        AnnouncementCommands::Download(opts) => {
+            download(ctx, cmd.force, course, cmd.otp_code, opts).await?
+        }
+        AnnouncementCommands::MarkRead { ids, all, all_term } => {
+            mark_read(ctx, cmd.force, !all_term, course, ids, all, cmd.otp_code).await?
         }
     }
     Ok(())
@@ -51,19 +171,35 @@ pub async fn run(cmd: CommandAnnouncement, ctx: &CommandCtx<'_>) -> anyhow::Resu
 
 type AnnouncementListItem = (Arc<Course>, String, CourseAnnouncementHandle);
 
+pub struct ListFilter {
+    only_unread: bool,
+    since: Option<chrono::DateTime<chrono::FixedOffset>>,
+    until: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// 课程别名、课程 ID 或课程标题的子串/正则表达式
+    course: Option<String>,
+}
+
+impl ListFilter {
+    /// 指定了时间范围时，没有发布时间的公告不显示
+    fn contains_time(&self, a: &CourseAnnouncementHandle) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        let Some(t) = a.time() else {
+            return false;
+        };
+        self.since.is_none_or(|s| t >= s) && self.until.is_none_or(|u| t < u)
+    }
+}
+
 async fn get_announcements(
     course: &Course,
     pb: indicatif::ProgressBar,
 ) -> anyhow::Result<Vec<CourseAnnouncementHandle>> {
     let announcements = course
-        .list_announcements_from_coursepage()
+        .list_announcements()
         .await
-        .with_context(|| {
-            format!(
-                "fetch announcements from course page for {}",
-                course.meta().title()
-            )
-        })?;
+        .with_context(|| format!("fetch announcements for {}", course.meta().title()))?;
     pb.finish_with_message("done.");
     Ok(announcements)
 }
@@ -72,9 +208,10 @@ async fn get_courses_and_announcements(
     ctx: &CommandCtx<'_>,
     force: bool,
     cur_term: bool,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<Vec<(Course, Vec<CourseAnnouncementHandle>)>> {
-    let courses = load_courses(ctx, force, cur_term, otp_code).await?;
+    let courses = load_courses(ctx, force, cur_term, course, otp_code).await?;
 
     let pb = ctx
         .multi
@@ -111,36 +248,78 @@ pub async fn list(
     ctx: &CommandCtx<'_>,
     force: bool,
     cur_term: bool,
+    filter: ListFilter,
     otp_code: String,
 ) -> anyhow::Result<()> {
-    let courses = get_courses_and_announcements(ctx, force, cur_term, otp_code).await?;
-    let all_announcements = courses
+    let mut state = ReadState::load().await?;
+    let courses =
+        get_courses_and_announcements(ctx, force, cur_term, filter.course.as_deref(), otp_code)
+            .await?;
+    if state.migrate(courses.iter().flat_map(|(_, anns)| anns)) {
+        state.save().await?;
+    }
+
+    let unread_counts = courses
+        .iter()
+        .map(|(course, announcements)| {
+            let n = announcements.iter().filter(|a| !state.is_read(a)).count();
+            (course.meta().name().to_owned(), n)
+        })
+        .filter(|(_, n)| *n > 0)
+        .collect::<Vec<_>>();
+
+    let mut announcements = courses
         .iter()
         .flat_map(|(course, announcements)| {
             announcements.iter().map(move |announcement| {
                 (course.to_owned(), announcement.id(), announcement.clone())
             })
         })
+        .filter(|(_, _, a)| !filter.only_unread || !state.is_read(a))
+        .filter(|(_, _, a)| filter.contains_time(a))
         .collect::<Vec<_>>();
 
-    let announcements = sort_announcements_owned(all_announcements);
-    list_brief(announcements).await
+    sort_by_time(&mut announcements);
+    list_brief(announcements, &unread_counts, &state, filter.only_unread).await
 }
 
-async fn list_brief(items: Vec<(Course, String, CourseAnnouncementHandle)>) -> anyhow::Result<()> {
+async fn list_brief(
+    items: Vec<(Course, String, CourseAnnouncementHandle)>,
+    unread_counts: &[(String, usize)],
+    state: &ReadState,
+    only_unread: bool,
+) -> anyhow::Result<()> {
     let mut outbuf = Vec::new();
-    let title = "课程公告";
+    let title = if only_unread {
+        "未读课程公告"
+    } else {
+        "课程公告"
+    };
     let total = items.len();
     writeln!(outbuf, "{D}>{D:#} {B}{title} ({total}){B:#} {D}<{D:#}\n")?;
 
+    if !unread_counts.is_empty() {
+        for (name, n) in unread_counts {
+            writeln!(outbuf, "{BL}{B}{name}{B:#}{BL:#}: {RD}{n}{RD:#} 条未读")?;
+        }
+        writeln!(outbuf)?;
+    }
+
     for (idx, (course, id, announcement)) in items.iter().enumerate() {
         write!(outbuf, "{GR}[{:>2}]{GR:#} ", idx + 1)?;
+        if !state.is_read(announcement) {
+            write!(outbuf, "{RD}●{RD:#} ")?;
+        }
         write!(
             outbuf,
             "{BL}{B}{}{B:#}{BL:#} {D}>{D:#} {}",
             course.meta().name(),
             announcement.title()
         )?;
+        if let Some(t) = announcement.time() {
+            let t = t.with_timezone(&chrono::Local).format("%m-%d %H:%M");
+            write!(outbuf, " {D}({t}){D:#}")?;
+        }
         let att_count = announcement.attachments().len();
         if att_count > 0 {
             write!(outbuf, " ({GR}{att_count} 个附件{GR:#})")?;
@@ -156,10 +335,11 @@ pub async fn show(
     ctx: &CommandCtx<'_>,
     force: bool,
     cur_term: bool,
+    course: Option<&str>,
     id: &str,
     otp_code: String,
 ) -> anyhow::Result<()> {
-    let items = fetch_announcements(ctx, force, cur_term, otp_code).await?;
+    let items = fetch_announcements(ctx, force, cur_term, course, otp_code).await?;
     let Some((course, ann_id, announcement)) =
         items.into_iter().find(|(_, ann_id, _)| ann_id == id)
     else {
@@ -170,29 +350,90 @@ pub async fn show(
     writeln!(outbuf, "{D}>{D:#} {B}公告详情{B:#} {D}<{D:#}\n")?;
     write_announcement_detail(&mut outbuf, &ann_id, &course, &announcement).context("io error")?;
     buf_try!(@try fs::stdout().write_all(outbuf).await);
+
+    let mut state = ReadState::load().await?;
+    if state.mark_read(&announcement) {
+        state.save().await?;
+    }
     Ok(())
 }
 
-fn sort_announcements_owned(
-    mut items: Vec<(Course, String, CourseAnnouncementHandle)>,
-) -> Vec<(Course, String, CourseAnnouncementHandle)> {
-    items.sort_by(|a, b| match (b.2.time(), a.2.time()) {
-        (Some(time_b), Some(time_a)) => time_b.cmp(time_a),
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
-    });
-    items
+async fn download(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    course: Option<&str>,
+    otp_code: String,
+    opts: DownloadOptions,
+) -> anyhow::Result<()> {
+    let (id, outdir) = (opts.id.as_str(), opts.outdir.as_path());
+    let items = fetch_announcements(ctx, force, !opts.all_term, course, otp_code).await?;
+    let Some((course, _, announcement)) = items.into_iter().find(|(_, ann_id, _)| ann_id == id)
+    else {
+        anyhow::bail!("announcement with id {} not found", id);
+    };
+
+    let atts = announcement
+        .attachments()
+        .iter()
+        .chain(announcement.embedded())
+        .collect::<Vec<_>>();
+    if atts.is_empty() {
+        println!("公告 {} 没有附件", announcement.title());
+        return Ok(());
+    }
+    if !outdir.exists() {
+        fs::create_dir_all(outdir).await?;
+    }
+
+    download_attachments(
+        ctx,
+        course.client(),
+        &atts,
+        outdir,
+        opts.on_conflict,
+        opts.jobs,
+    )
+    .await?;
+
+    println!("Done.");
+    Ok(())
 }
 
-fn sort_announcements_items(mut items: Vec<AnnouncementListItem>) -> Vec<AnnouncementListItem> {
-    items.sort_by(|a, b| match (b.2.time(), a.2.time()) {
-        (Some(time_b), Some(time_a)) => time_b.cmp(time_a),
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
-    });
-    items
+async fn mark_read(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    cur_term: bool,
+    course: Option<&str>,
+    ids: Vec<String>,
+    all: bool,
+    otp_code: String,
+) -> anyhow::Result<()> {
+    let known = fetch_announcements(ctx, force, cur_term, course, otp_code).await?;
+    let anns = if all {
+        known.iter().map(|(_, _, a)| a).collect::<Vec<_>>()
+    } else {
+        ids.iter()
+            .map(|id| {
+                known
+                    .iter()
+                    .find(|(_, ann_id, _)| ann_id == id)
+                    .map(|(_, _, a)| a)
+                    .with_context(|| format!("announcement with id {id} not found"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?
+    };
+
+    let mut state = ReadState::load().await?;
+    state.migrate(known.iter().map(|(_, _, a)| a));
+    let n = anns.iter().filter(|a| state.mark_read(a)).count();
+    state.save().await?;
+    println!("已将 {B}{n}{B:#} 条公告标记为已读");
+    Ok(())
+}
+
+/// 按发布时间从新到旧排序，没有发布时间的排在最后
+fn sort_by_time<C>(items: &mut [(C, String, CourseAnnouncementHandle)]) {
+    items.sort_by_cached_key(|(_, _, a)| std::cmp::Reverse(a.time()));
 }
 
 fn write_announcement_detail(
@@ -209,22 +450,34 @@ fn write_announcement_detail(
     )?;
     writeln!(buf, "{D}ID:{D:#} {id}")?;
 
-    if let Some(time) = announcement.time() {
-        writeln!(buf, "{D}发布时间:{D:#} {time}")?;
+    match (announcement.time(), announcement.time_raw()) {
+        (Some(t), _) => writeln!(
+            buf,
+            "{D}发布时间:{D:#} {}",
+            t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        )?,
+        (None, Some(raw)) => writeln!(buf, "{D}发布时间:{D:#} {raw}")?,
+        (None, None) => {}
+    }
+    if let Some(author) = announcement.author() {
+        writeln!(buf, "{D}发布者:{D:#} {author}")?;
     }
 
     if !announcement.descriptions().is_empty() {
         writeln!(buf)?;
         for line in announcement.descriptions() {
-            writeln!(buf, "{line}")?;
+            writeln!(buf, "{}", markdown::render_terminal(line))?;
         }
     }
 
-    if !announcement.attachments().is_empty() {
+    if !announcement.attachments().is_empty() || !announcement.embedded().is_empty() {
         writeln!(buf)?;
         for (name, _) in announcement.attachments() {
             writeln!(buf, "{D}[附件]{D:#} {UL}{name}{UL:#}")?;
         }
+        for (name, _) in announcement.embedded() {
+            writeln!(buf, "{D}[内嵌]{D:#} {UL}{name}{UL:#}")?;
+        }
     }
 
     writeln!(buf)?;
@@ -235,9 +488,10 @@ async fn fetch_announcements(
     ctx: &CommandCtx<'_>,
     force: bool,
     cur_term: bool,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<Vec<AnnouncementListItem>> {
-    let courses = get_courses_and_announcements(ctx, force, cur_term, otp_code).await?;
+    let courses = get_courses_and_announcements(ctx, force, cur_term, course, otp_code).await?;
 
     let mut all_announcements = courses
         .into_iter()
@@ -249,6 +503,6 @@ async fn fetch_announcements(
         })
         .collect::<Vec<_>>();
 
-    all_announcements = sort_announcements_items(all_announcements);
+    sort_by_time(&mut all_announcements);
     Ok(all_announcements)
 }
@@ -10,6 +10,10 @@ pub struct CommandAssignment {
     #[arg(short, long, default_value = "false")]
     force: bool,
 
+    /// 只处理指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, global = true)]
+    course: Option<String>,
+
     #[command(subcommand)]
     command: AssignmentCommands,
 
@@ -34,17 +38,7 @@ enum AssignmentCommands {
     ///
     /// 如果没有指定作业 ID，则会启用交互式模式，列出所有作业供用户选择
     #[command(visible_alias("down"))]
-    Download {
-        /// (Optionl) 作业 ID (ID 形如 `f4f30444c7485d49`, 可通过 `pku3b assignment list` 查看)
-        #[arg(group = "download-type")]
-        id: Option<String>,
-        /// 文件下载目录 (支持相对路径)
-        #[arg(short, long, default_value = ".")]
-        dir: std::path::PathBuf,
-        /// 在所有学期的作业范围中查找
-        #[arg(long, default_value = "false")]
-        all_term: bool,
-    },
+    Download(DownloadOptions),
     /// 提交课程作业
     ///
     /// 如果没有指定作业 ID，则会启用交互式模式，列出所有作业供用户选择
@@ -59,25 +53,44 @@ enum AssignmentCommands {
     },
 }
 
+#[derive(clap::Args)]
+struct DownloadOptions {
+    /// (Optionl) 作业 ID (ID 形如 `f4f30444c7485d49`, 可通过 `pku3b assignment list` 查看)
+    #[arg(group = "download-type")]
+    id: Option<String>,
+    /// 文件下载目录 (支持相对路径)
+    #[arg(short, long, default_value = ".")]
+    dir: std::path::PathBuf,
+    /// 在所有学期的作业范围中查找
+    #[arg(long, default_value = "false")]
+    all_term: bool,
+    /// 目标文件已存在时的处理方式
+    #[arg(long, value_enum, default_value = "overwrite")]
+    on_conflict: OnConflict,
+    /// 同时下载的附件数
+    #[arg(short, long, default_value = "4")]
+    jobs: usize,
+}
+
 pub async fn run(cmd: CommandAssignment, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    let course = cmd.course.as_deref();
     match cmd.command {
         AssignmentCommands::List { all, all_term } => {
-            list(ctx, cmd.force, all || all_term, !all_term, cmd.otp_code).await?
-        }
-        AssignmentCommands::Download { id, dir, all_term } => {
-            download(
+            list(
                 ctx,
-                id.as_deref(),
-                &dir,
                 cmd.force,
-                all_term,
+                all || all_term,
                 !all_term,
+                course,
                 cmd.otp_code,
             )
             .await?
         }
+        AssignmentCommands::Download(opts) => {
+            download(ctx, cmd.force, course, cmd.otp_code, opts).await?
+        }
         AssignmentCommands::Submit { id, path } => {
-            submit(ctx, id.as_deref(), path.as_deref(), cmd.otp_code).await?
+            submit(ctx, id.as_deref(), path.as_deref(), course, cmd.otp_code).await?
         }
     }
     Ok(())
@@ -117,25 +130,23 @@ async fn get_contents(
     Ok(data.into_iter().map(|data| c.build_content(data)).collect())
 }
 
-async fn get_assignments(
-    c: &Course,
-    pb: indicatif::ProgressBar,
-) -> anyhow::Result<Vec<CourseAssignmentHandle>> {
-    let r = get_contents(c, pb)
-        .await?
-        .into_iter()
-        .filter_map(|c| c.into_assignment_opt())
-        .collect();
-    Ok(r)
-}
+/// 课程及其作业、测验 (均附带 ID)
+pub type CourseItems = (
+    Course,
+    Vec<(String, CourseAssignment)>,
+    Vec<(String, CourseQuiz)>,
+);
 
-async fn get_courses_and_assignments(
+/// 获取课程及其作业. `with_quizzes` 为真时同时获取课程中的测验.
+pub async fn get_courses_and_assignments(
     ctx: &CommandCtx<'_>,
     force: bool,
     cur_term: bool,
+    course: Option<&str>,
+    with_quizzes: bool,
     otp_code: String,
-) -> anyhow::Result<Vec<(Course, Vec<(String, CourseAssignment)>)>> {
-    let courses = load_courses(ctx, force, cur_term, otp_code).await?;
+) -> anyhow::Result<Vec<CourseItems>> {
+    let courses = load_courses(ctx, force, cur_term, course, otp_code).await?;
 
     // fetch each course concurrently
     let pb = ctx
@@ -144,7 +155,7 @@ async fn get_courses_and_assignments(
         .with_prefix("All");
     let futs = courses.into_iter().map(async |c| -> anyhow::Result<_> {
         let c = c.get().await.context("fetch course")?;
-        let assignments = get_assignments(
+        let contents = get_contents(
             &c,
             ctx.multi
                 .add(pbar::new(0).with_prefix(c.meta().name().to_owned())),
@@ -152,7 +163,16 @@ async fn get_courses_and_assignments(
         .await
         .with_context(|| format!("fetch assignment handles of {}", c.meta().title()))?;
 
-        pb.inc_length(assignments.len() as u64);
+        let mut assignments = Vec::new();
+        let mut quizzes = Vec::new();
+        for ct in contents {
+            match ct.kind() {
+                CourseContentKind::Quiz if with_quizzes => quizzes.extend(ct.into_quiz_opt()),
+                _ => assignments.extend(ct.into_assignment_opt()),
+            }
+        }
+
+        pb.inc_length((assignments.len() + quizzes.len()) as u64);
         let futs = assignments.into_iter().map(async |a| -> anyhow::Result<_> {
             let id = a.id();
             let r = a.get().await.context("fetch assignment")?;
@@ -161,8 +181,23 @@ async fn get_courses_and_assignments(
         });
         let assignments = try_join_all(futs).await?;
 
+        let quizzes = match quizzes.is_empty() {
+            true => Vec::new(),
+            false => {
+                // 同一课程的测验共用成绩列，只获取一次
+                let gradebook = c.quiz_gradebook().await;
+                let futs = quizzes.into_iter().map(async |q| -> anyhow::Result<_> {
+                    let id = q.id();
+                    let r = q.get(&gradebook).await.context("fetch quiz")?;
+                    pb.inc(1);
+                    Ok((id, r))
+                });
+                try_join_all(futs).await?
+            }
+        };
+
         pb.inc(1);
-        Ok((c, assignments))
+        Ok((c, assignments, quizzes))
     });
     let courses = try_join_all(futs).await?;
     pb.finish_and_clear();
@@ -176,37 +211,53 @@ pub async fn list(
     force: bool,
     all: bool,
     cur_term: bool,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<()> {
-    let courses = get_courses_and_assignments(ctx, force, cur_term, otp_code).await?;
+    let courses = get_courses_and_assignments(ctx, force, cur_term, course, true, otp_code).await?;
 
-    let mut all_assignments = courses
-        .iter()
-        .flat_map(|(c, assignments)| {
+    enum Item<'a> {
+        Assignment(&'a CourseAssignment),
+        Quiz(&'a CourseQuiz),
+    }
+
+    let mut all_items = Vec::new();
+    for (c, assignments, quizzes) in &courses {
+        all_items.extend(
             assignments
                 .iter()
-                .map(move |(id, a)| (c.to_owned(), id.to_owned(), a))
-        })
-        // retain only unfinished assignments if not in full mode
-        .filter(|(_, _, a)| all || a.last_attempt().is_none())
-        .collect::<Vec<_>>();
+                // retain only unfinished assignments if not in full mode
+                .filter(|(_, a)| all || a.last_attempt().is_none())
+                .map(|(id, a)| (c, id, Item::Assignment(a), a.deadline())),
+        );
+        all_items.extend(
+            quizzes
+                .iter()
+                .filter(|(_, q)| all || !q.is_attempted())
+                .map(|(id, q)| (c, id, Item::Quiz(q), q.deadline())),
+        );
+    }
 
     // sort by deadline
     log::debug!("sorting assignments...");
-    all_assignments.sort_by_cached_key(|(_, _, a)| a.deadline());
+    all_items.sort_by(|a, b| a.3.cmp(&b.3));
 
     // prepare output statements
     let mut outbuf = Vec::new();
     let title = if all {
-        "所有作业 (包括已完成)"
+        "所有作业/测验 (包括已完成)"
     } else {
-        "未完成作业"
+        "未完成作业/测验"
     };
-    let total = all_assignments.len();
+    let total = all_items.len();
     writeln!(outbuf, "{D}>{D:#} {B}{title} ({total}){B:#} {D}<{D:#}\n")?;
 
-    for (c, id, a) in all_assignments {
-        write_course_assignment(&mut outbuf, &id, &c, a).context("io error")?;
+    for (c, id, item, _) in all_items {
+        match item {
+            Item::Assignment(a) => write_course_assignment(&mut outbuf, id, c, a),
+            Item::Quiz(q) => cmd_quiz::write_course_quiz(&mut outbuf, id, c, q),
+        }
+        .context("io error")?;
     }
 
     // write to stdout
@@ -222,13 +273,15 @@ async fn fetch_assignments(
     force: bool,
     all: bool,
     cur_term: bool,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<Vec<AssignmentListItem>> {
-    let courses = get_courses_and_assignments(ctx, force, cur_term, otp_code).await?;
+    let courses =
+        get_courses_and_assignments(ctx, force, cur_term, course, false, otp_code).await?;
 
     let mut all_assignments = courses
         .into_iter()
-        .flat_map(|(c, assignments)| {
+        .flat_map(|(c, assignments, _)| {
             let c = Arc::new(c);
             assignments
                 .into_iter()
@@ -268,17 +321,17 @@ async fn select_assignment(
     Ok(r)
 }
 
-pub async fn download(
+async fn download(
     ctx: &CommandCtx<'_>,
-    id: Option<&str>,
-    dir: &std::path::Path,
     force: bool,
-    all: bool,
-    cur_term: bool,
+    course: Option<&str>,
     otp_code: String,
+    opts: DownloadOptions,
 ) -> anyhow::Result<()> {
-    let items = fetch_assignments(ctx, force, all, cur_term, otp_code).await?;
-    let a = match id {
+    // 在所有学期中查找时包括已完成的作业
+    let items =
+        fetch_assignments(ctx, force, opts.all_term, !opts.all_term, course, otp_code).await?;
+    let (c, _, a) = match opts.id.as_deref() {
         Some(id) => match items.into_iter().find(|x| x.1 == id) {
             Some(r) => r,
             None => anyhow::bail!("assignment with id {} not found", id),
@@ -286,33 +339,26 @@ pub async fn download(
         None => select_assignment(items).await?,
     };
 
-    let sp = ctx.spinner();
-    download_data(&sp, dir, &a.2).await?;
-    ctx.remove_spinner(sp);
-
-    Ok(())
+    download_data(ctx, c.client(), &opts, &a).await
 }
 
 async fn download_data(
-    sp: &pbar::AsyncSpinner,
-    dir: &std::path::Path,
+    ctx: &CommandCtx<'_>,
+    client: &api::Client,
+    opts: &DownloadOptions,
     a: &CourseAssignment,
 ) -> anyhow::Result<()> {
+    let dir = opts.dir.as_path();
     if !dir.exists() {
         compio::fs::create_dir_all(dir).await?;
     }
 
-    let atts = a.attachments();
-    let tot = atts.len();
-    for (id, (name, uri)) in atts.iter().enumerate() {
-        sp.set_message(format!(
-            "[{}/{tot}] downloading attachment '{name}'...",
-            id + 1
-        ));
-        a.download_attachment(uri, &dir.join(name))
-            .await
-            .with_context(|| format!("download attachment '{name}'"))?;
-    }
+    let atts = a
+        .attachments()
+        .iter()
+        .chain(a.embedded())
+        .collect::<Vec<_>>();
+    download_attachments(ctx, client, &atts, dir, opts.on_conflict, opts.jobs).await?;
 
     println!("Done.");
     Ok(())
@@ -322,11 +368,12 @@ pub async fn submit(
     ctx: &CommandCtx<'_>,
     id: Option<&str>,
     path: Option<&std::path::Path>,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<()> {
-    let items = fetch_assignments(ctx, false, false, true, otp_code).await?;
+    let items = fetch_assignments(ctx, false, false, true, course, otp_code).await?;
 
-    let (c, _, a) = match id {
+    let (c, id, a) = match id {
         Some(id) => match items.into_iter().find(|x| x.1 == id) {
             Some(r) => r,
             None => anyhow::bail!("assignment with id {} not found", id),
@@ -365,14 +412,26 @@ pub async fn submit(
         anyhow::bail!("file not found: {:?}", path);
     }
 
+    let cfg = config::read_cfg(&ctx.config_path)
+        .await
+        .context("read config file")?;
+    let content_type = mime::detect(&path, &cfg.mime_types.unwrap_or_default())
+        .await
+        .with_context(|| format!("read {:?}", path.display()))?;
+
     let sp = ctx.spinner();
     sp.set_message("submit file...");
-    a.submit_file(path.as_path())
+    let receipt = a
+        .submit_file(path.as_path(), &content_type)
         .await
         .with_context(|| format!("submit {:?} to {:?}", path.display(), a.title()))?;
 
     ctx.remove_spinner(sp);
 
+    let receipt_path = write_receipt(&id, &receipt)
+        .await
+        .context("write submission receipt")?;
+
     println!(
         "成功将 {GR}{H2}{}{H2:#}{GR:#} 提交至 {MG}{H1}{} {}{H1:#}{MG:#} 课程作业",
         path.display(),
@@ -380,10 +439,32 @@ pub async fn submit(
         a.title()
     );
 
+    println!(
+        "{D}sha256: {} ({} bytes), 回执已保存至 {}{D:#}",
+        receipt.sha256,
+        receipt.size,
+        receipt_path.display()
+    );
+
     println!("{EM:}tips: 执行 {H2}pku3b a -f ls -a{H2:#} 可强制刷新缓存并查看作业完成状态{EM:#}");
     Ok(())
 }
 
+async fn write_receipt(
+    id: &str,
+    receipt: &SubmissionReceipt,
+) -> anyhow::Result<std::path::PathBuf> {
+    let dir = utils::data_dir().join("receipts");
+    fs::create_dir_all(&dir).await?;
+
+    let now = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("{id}-{now}.json"));
+    let buf = serde_json::to_vec_pretty(receipt)?;
+    buf_try!(@try fs::write(&path, buf).await);
+
+    Ok(path)
+}
+
 fn write_assignment_title_ln(
     buf: &mut Vec<u8>,
     id: &str,
@@ -395,8 +476,7 @@ fn write_assignment_title_ln(
     if let Some(att) = a.last_attempt() {
         write!(buf, " ({GR}已完成: {att}{GR:#})")?;
     } else if let Some(t) = a.deadline() {
-        let delta = t - chrono::Local::now();
-        write!(buf, " ({})", fmt_time_delta(delta))?;
+        write!(buf, " ({})", fmt_time_delta(t.remaining()))?;
     } else if let Some(raw) = a.deadline_raw() {
         write!(buf, " ({raw})")?;
     } else {
@@ -413,18 +493,29 @@ fn write_course_assignment(
     a: &CourseAssignment,
 ) -> std::io::Result<()> {
     write_assignment_title_ln(buf, id, c, a)?;
+    if let Some(d) = a.deadline() {
+        writeln!(
+            buf,
+            "{D}截止时间:{D:#} {} {D}({}){D:#}",
+            d.local().format("%Y-%m-%d %H:%M (UTC%:z)"),
+            d.raw()
+        )?;
+    }
 
     if !a.descriptions().is_empty() {
         writeln!(buf)?;
         for p in a.descriptions() {
-            writeln!(buf, "{p}")?;
+            writeln!(buf, "{}", markdown::render_terminal(p))?;
         }
     }
-    if !a.attachments().is_empty() {
+    if !a.attachments().is_empty() || !a.embedded().is_empty() {
         writeln!(buf)?;
         for (name, _) in a.attachments() {
             writeln!(buf, "{D}[附件]{D:#} {UL}{name}{UL:#}")?;
         }
+        for (name, _) in a.embedded() {
+            writeln!(buf, "{D}[内嵌]{D:#} {UL}{name}{UL:#}")?;
+        }
     }
     writeln!(buf)?;
 
@@ -0,0 +1,219 @@
+use super::*;
+
+#[derive(clap::Args)]
+pub struct CommandCalendar {
+    /// 强制刷新
+    #[arg(short, long, default_value = "false")]
+    force: bool,
+
+    /// 只显示指定课程的事件和截止时间：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, global = true)]
+    course: Option<String>,
+
+    #[command(subcommand)]
+    command: CalendarCommands,
+
+    /// 手机令牌码。当需要使用 OTP 登录，但未提供此参数时，将会从命令行交互式读取 OTP 码。
+    #[arg(long, default_value = "")]
+    otp_code: String,
+}
+
+#[derive(Subcommand)]
+enum CalendarCommands {
+    /// 按时间顺序列出日历事件和作业/测验截止时间
+    #[command(visible_alias("ls"))]
+    List {
+        /// 起始时间，默认为今天. 支持相对时间 (如 `3d` 表示三天前、`+1d` 表示一天后)、
+        /// 日期 (如 2025-03-01) 或具体时间
+        #[arg(long)]
+        from: Option<TimeBound>,
+
+        /// 结束时间，格式同 `--from`，日期包括当天. 默认为两周后
+        #[arg(long)]
+        to: Option<TimeBound>,
+
+        /// 不获取课程作业和测验，只显示日历中的事件 (更快)
+        #[arg(long, default_value = "false")]
+        no_assignments: bool,
+    },
+}
+
+pub async fn run(cmd: CommandCalendar, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    match cmd.command {
+        CalendarCommands::List {
+            from,
+            to,
+            no_assignments,
+        } => {
+            let today = chrono::Utc::now()
+                .with_timezone(&api::blackboard::shanghai())
+                .date_naive();
+            let from = from.unwrap_or(TimeBound::Day(today)).start();
+            let to = match to {
+                Some(t) => t.end(),
+                None => TimeBound::Day(today + chrono::TimeDelta::weeks(2)).end(),
+            };
+            anyhow::ensure!(from < to, "--from must be earlier than --to");
+            let course = cmd.course.as_deref();
+            list(
+                ctx,
+                cmd.force,
+                from,
+                to,
+                !no_assignments,
+                course,
+                cmd.otp_code,
+            )
+            .await?
+        }
+    }
+    Ok(())
+}
+
+/// 日程中的一项
+enum AgendaItem<'a> {
+    Event(&'a CalendarItem),
+    Assignment(&'a Course, &'a str, &'a CourseAssignment),
+    Quiz(&'a Course, &'a str, &'a CourseQuiz),
+}
+
+async fn list(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    from: chrono::DateTime<chrono::FixedOffset>,
+    to: chrono::DateTime<chrono::FixedOffset>,
+    with_assignments: bool,
+    course: Option<&str>,
+    otp_code: String,
+) -> anyhow::Result<()> {
+    let (b, sp) = load_blackboard(ctx, !force, otp_code.clone()).await?;
+    sp.set_message("fetching calendar items...");
+    let mut events = b
+        .calendar_items(from, to)
+        .await
+        .context("fetch calendar items")?;
+    ctx.remove_spinner(sp);
+
+    // 指定课程时只保留这些课程的日历事件
+    if course.is_some() {
+        let ids = load_courses(ctx, force, false, course, otp_code.clone())
+            .await?
+            .iter()
+            .map(|c| c.id().to_owned())
+            .collect::<Vec<_>>();
+        events.retain(|e| ids.iter().any(|id| id == e.calendar_id()));
+    }
+
+    let courses = if with_assignments {
+        cmd_assignment::get_courses_and_assignments(ctx, force, true, course, true, otp_code)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    let in_range = |t: &chrono::DateTime<chrono::FixedOffset>| from <= *t && *t < to;
+    let mut agenda = Vec::new();
+    for (c, assignments, quizzes) in &courses {
+        agenda.extend(assignments.iter().filter_map(|(id, a)| {
+            let t = a.deadline()?.time();
+            in_range(&t).then_some((t, AgendaItem::Assignment(c, id, a)))
+        }));
+        agenda.extend(quizzes.iter().filter_map(|(id, q)| {
+            let t = q.deadline()?.time();
+            in_range(&t).then_some((t, AgendaItem::Quiz(c, id, q)))
+        }));
+    }
+    // 日历中成绩项的截止时间与作业/测验重复时，只保留作业/测验
+    let duplicated = |e: &CalendarItem| {
+        e.is_due()
+            && agenda.iter().any(|(t, item)| {
+                let (c, title) = match item {
+                    AgendaItem::Assignment(c, _, a) => (c, a.title()),
+                    AgendaItem::Quiz(c, _, q) => (c, q.title()),
+                    AgendaItem::Event(_) => return false,
+                };
+                title == e.title()
+                    && e.calendar_name().contains(c.meta().name())
+                    && (*t - e.start()).abs() <= chrono::TimeDelta::minutes(1)
+            })
+    };
+    let events = events
+        .iter()
+        .filter(|e| !duplicated(e))
+        .map(|e| (e.start(), AgendaItem::Event(e)))
+        .collect::<Vec<_>>();
+    agenda.extend(events);
+    agenda.sort_by_key(|(t, _)| *t);
+
+    let mut outbuf = Vec::new();
+    writeln!(
+        outbuf,
+        "{D}>{D:#} {B}日程 ({}){B:#} {D}<{D:#}",
+        agenda.len()
+    )?;
+
+    let mut last_day = None;
+    for (t, item) in agenda {
+        let t = t.with_timezone(&chrono::Local);
+        let day = t.date_naive();
+        if last_day != Some(day) {
+            writeln!(outbuf, "\n{BL}{H1}{}{H1:#}{BL:#}", t.format("%Y-%m-%d %a"))?;
+            last_day = Some(day);
+        }
+
+        match item {
+            AgendaItem::Event(e) => {
+                write!(outbuf, "{D}•{D:#} {}", t.format("%H:%M"))?;
+                if let Some(end) = e.end().filter(|end| *end > e.start()) {
+                    let end = end.with_timezone(&chrono::Local);
+                    let fmt = if end.date_naive() == day {
+                        "%H:%M"
+                    } else {
+                        "%m-%d %H:%M"
+                    };
+                    write!(outbuf, "-{}", end.format(fmt))?;
+                }
+                write!(outbuf, " {D}[{}]{D:#} {B}{}{B:#}", e.kind(), e.title())?;
+                if !e.calendar_name().is_empty() {
+                    write!(outbuf, " {D}({}){D:#}", e.calendar_name())?;
+                }
+                if let Some(loc) = e.location() {
+                    write!(outbuf, " @ {loc}")?;
+                }
+                writeln!(outbuf)?;
+                if let Some(desc) = e.description().and_then(|d| d.lines().next()) {
+                    writeln!(outbuf, "  {D}{}{D:#}", markdown::render_terminal(desc))?;
+                }
+            }
+            AgendaItem::Assignment(c, id, a) => {
+                write!(
+                    outbuf,
+                    "{D}•{D:#} {} {D}[作业]{D:#} {B}{}{B:#} {D}({}){D:#}",
+                    t.format("%H:%M"),
+                    a.title(),
+                    c.meta().name()
+                )?;
+                if a.last_attempt().is_some() {
+                    write!(outbuf, " {GR}已提交{GR:#}")?;
+                }
+                writeln!(outbuf, " {D}{id}{D:#}")?;
+            }
+            AgendaItem::Quiz(c, id, q) => {
+                write!(
+                    outbuf,
+                    "{D}•{D:#} {} {D}[测验]{D:#} {B}{}{B:#} {D}({}){D:#}",
+                    t.format("%H:%M"),
+                    q.title(),
+                    c.meta().name()
+                )?;
+                if q.is_attempted() {
+                    write!(outbuf, " {GR}已完成{GR:#}")?;
+                }
+                writeln!(outbuf, " {D}{id}{D:#}")?;
+            }
+        }
+    }
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use super::*;
+
+#[derive(clap::Args)]
+pub struct CommandCourse {
+    /// 强制刷新
+    #[arg(short, long, default_value = "false")]
+    force: bool,
+
+    #[command(subcommand)]
+    command: CourseCommands,
+
+    /// 手机令牌码。当需要使用 OTP 登录，但未提供此参数时，将会从命令行交互式读取 OTP 码。
+    #[arg(long, default_value = "")]
+    otp_code: String,
+}
+
+#[derive(Subcommand)]
+enum CourseCommands {
+    /// 查看课程的教师、助教及其联系方式、上课时间和课程简介
+    Info {
+        /// 课程别名、课程 ID 或课程标题的子串/正则表达式
+        course: String,
+
+        /// 在所有学期的课程中查找
+        #[arg(long, default_value = "false")]
+        all_term: bool,
+    },
+}
+
+pub async fn run(cmd: CommandCourse, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    match cmd.command {
+        CourseCommands::Info { course, all_term } => {
+            info(ctx, cmd.force, &course, !all_term, cmd.otp_code).await?
+        }
+    }
+    Ok(())
+}
+
+async fn info(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    course: &str,
+    cur_term: bool,
+    otp_code: String,
+) -> anyhow::Result<()> {
+    let courses = load_courses(ctx, force, cur_term, Some(course), otp_code).await?;
+
+    let pb = ctx
+        .multi
+        .add(pbar::new(courses.len() as u64))
+        .with_prefix("All");
+    let futs = courses.into_iter().map(async |c| -> anyhow::Result<_> {
+        let c = c.get().await.context("fetch course")?;
+        let info = c
+            .info()
+            .await
+            .with_context(|| format!("fetch info of {}", c.meta().title()))?;
+        pb.inc(1);
+        Ok((c, info))
+    });
+    let courses = try_join_all(futs).await?;
+    pb.finish_and_clear();
+    ctx.multi.remove(&pb);
+
+    let mut outbuf = Vec::new();
+    writeln!(outbuf, "{D}>{D:#} {B}课程信息{B:#} {D}<{D:#}\n")?;
+
+    for (c, info) in courses {
+        writeln!(outbuf, "{BL}{H1}[{}]{H1:#}{BL:#}\n", c.meta().title())?;
+
+        if let Some(id) = c.meta().external_id() {
+            writeln!(outbuf, "{B}课程编号{B:#} {id}\n")?;
+        }
+
+        if let Some(term) = c.meta().term() {
+            write!(outbuf, "{B}学期{B:#} {}", term.name())?;
+            if let (Some(start), Some(end)) = (term.start(), term.end()) {
+                let fmt = |t: chrono::DateTime<chrono::FixedOffset>| {
+                    t.with_timezone(&chrono::Local).format("%Y-%m-%d")
+                };
+                write!(outbuf, " {D}({} ~ {}){D:#}", fmt(start), fmt(end))?;
+            }
+            writeln!(outbuf, "\n")?;
+        }
+
+        writeln!(outbuf, "{B}教学人员{B:#}")?;
+        if info.staff().is_empty() {
+            writeln!(outbuf, "{D}(无){D:#}")?;
+        }
+        for s in info.staff() {
+            write!(outbuf, "{D}•{D:#} {}", s.name())?;
+            if let Some(role) = s.role() {
+                write!(outbuf, " {D}({role}){D:#}")?;
+            }
+            if let Some(email) = s.email() {
+                write!(outbuf, " {UL}{email}{UL:#}")?;
+            }
+            writeln!(outbuf)?;
+            for line in s.details() {
+                if s.email().is_some_and(|e| line.contains(e)) {
+                    continue;
+                }
+                writeln!(outbuf, "  {D}{}{D:#}", markdown::render_terminal(line))?;
+            }
+        }
+        writeln!(outbuf)?;
+
+        if !info.meeting_times().is_empty() {
+            writeln!(outbuf, "{B}上课时间{B:#}")?;
+            for t in info.meeting_times() {
+                writeln!(outbuf, "{D}•{D:#} {}", markdown::render_terminal(t))?;
+            }
+            writeln!(outbuf)?;
+        }
+
+        if !info.description().is_empty() {
+            writeln!(outbuf, "{B}课程简介{B:#}")?;
+            for para in info.description() {
+                writeln!(outbuf, "{}", markdown::render_terminal(para))?;
+            }
+            writeln!(outbuf)?;
+        }
+    }
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+    Ok(())
+}
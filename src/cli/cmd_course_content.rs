@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::Context;
 
 use super::*;
@@ -29,6 +31,33 @@ enum CourseContentCommands {
     /// 可用 `--output-desc` 将描述另存为文本；未指定 `-o` 时保存到当前目录。
     #[command(visible_alias("down"))]
     Download(DownloadOptions),
+
+    /// 列出课程菜单中的所有入口 (课程信息、教学大纲、课程资源等)
+    Menu {
+        /// 课程别名、课程 ID 或课程标题的子串/正则表达式
+        course: String,
+
+        /// 在所有学期的课程中查找
+        #[arg(long, default_value = "false")]
+        all_term: bool,
+    },
+
+    /// 打开课程菜单中的入口，将页面正文显示为文本
+    Open {
+        /// 课程别名、课程 ID 或课程标题的子串/正则表达式
+        course: String,
+
+        /// 菜单项的名称 (或其子串)，或 `menu` 输出中的序号
+        entry: String,
+
+        /// 输出 Markdown 原文，便于保存到文件
+        #[arg(long, default_value = "false")]
+        markdown: bool,
+
+        /// 在所有学期的课程中查找
+        #[arg(long, default_value = "false")]
+        all_term: bool,
+    },
 }
 
 #[derive(clap::Args)]
@@ -36,9 +65,12 @@ pub struct ListOptions {
     /// 将课程查询范围扩大到所有学期
     #[arg(long, default_value = "false")]
     all_term: bool,
-    /// 指定课程标题的子串
+    /// 只处理指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, alias = "course-title")]
+    course: Option<String>,
+    /// 只显示指定路径下的内容，以课程菜单项开头，用 `/` 分隔 (如 `课件/第三周`)
     #[arg(long)]
-    course_title: Option<String>,
+    path: Option<ContentPath>,
 }
 
 pub async fn run(cmd: CommandCourseContent, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
@@ -47,7 +79,120 @@ pub async fn run(cmd: CommandCourseContent, ctx: &CommandCtx<'_>) -> anyhow::Res
         CourseContentCommands::Download(opts) => {
             download(ctx, cmd.force, cmd.otp_code, opts).await?
         }
+        CourseContentCommands::Menu { course, all_term } => {
+            menu(ctx, cmd.force, cmd.otp_code, &course, all_term).await?
+        }
+        CourseContentCommands::Open {
+            course,
+            entry,
+            markdown,
+            all_term,
+        } => {
+            open(
+                ctx,
+                cmd.force,
+                cmd.otp_code,
+                &course,
+                &entry,
+                markdown,
+                all_term,
+            )
+            .await?
+        }
+    }
+    Ok(())
+}
+
+async fn get_courses(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    otp_code: String,
+    course: &str,
+    all_term: bool,
+) -> anyhow::Result<Vec<Course>> {
+    let courses = load_courses(ctx, force, !all_term, Some(course), otp_code).await?;
+
+    try_join_all(courses.iter().map(|c| c.get()))
+        .await
+        .context("fetch course")
+}
+
+async fn menu(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    otp_code: String,
+    course: &str,
+    all_term: bool,
+) -> anyhow::Result<()> {
+    let courses = get_courses(ctx, force, otp_code, course, all_term).await?;
+
+    let mut outbuf = Vec::new();
+    for c in courses {
+        writeln!(outbuf, "{BL}{H1}[{}]{H1:#}{BL:#}\n", c.meta().title())?;
+        for (i, (name, uri)) in c.entries().iter().enumerate() {
+            writeln!(
+                outbuf,
+                "{D}{:>2}.{D:#} {} {D}{uri}{D:#}",
+                i + 1,
+                name.trim()
+            )?;
+        }
+        writeln!(outbuf)?;
     }
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+    Ok(())
+}
+
+async fn open(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    otp_code: String,
+    course: &str,
+    entry: &str,
+    raw_markdown: bool,
+    all_term: bool,
+) -> anyhow::Result<()> {
+    let courses = get_courses(ctx, force, otp_code, course, all_term).await?;
+    if courses.len() > 1 {
+        let titles = courses
+            .iter()
+            .map(|c| c.meta().title())
+            .collect::<Vec<_>>()
+            .join(", ");
+        anyhow::bail!("multiple courses match '{course}': {titles}");
+    }
+    let c = &courses[0];
+
+    let (name, uri) = entry
+        .parse::<usize>()
+        .ok()
+        .and_then(|i| c.entries().get(i.checked_sub(1)?))
+        .or_else(|| c.find_entry(entry))
+        .with_context(|| format!("no menu entry matches '{entry}' in {}", c.meta().title()))?;
+
+    let sp = ctx.spinner();
+    sp.set_message(format!("fetching {}...", name.trim()));
+    let md = c
+        .entry_page(uri)
+        .await
+        .with_context(|| format!("fetch page {}", name.trim()))?;
+    ctx.remove_spinner(sp);
+
+    let mut outbuf = Vec::new();
+    if raw_markdown {
+        writeln!(outbuf, "# {}\n\n{md}", name.trim())?;
+    } else {
+        writeln!(
+            outbuf,
+            "{BL}{B}{}{B:#}{BL:#} {D}>{D:#} {BL}{B}{}{B:#}{BL:#}\n",
+            c.meta().name(),
+            name.trim()
+        )?;
+        writeln!(outbuf, "{}", markdown::render_terminal(&md))?;
+    }
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
     Ok(())
 }
 
@@ -85,20 +230,15 @@ async fn get_contents(
     Ok(data.into_iter().map(|data| c.build_content(data)).collect())
 }
 
-async fn get_courses_contents(
+pub async fn get_courses_contents(
     ctx: &CommandCtx<'_>,
     force: bool,
     otp_code: String,
     all_term: bool,
-    course_title: Option<&str>,
+    course: Option<&str>,
     course_id: Option<&str>,
 ) -> anyhow::Result<Vec<(Course, Vec<CourseContent>)>> {
-    let mut courses = load_courses(ctx, force, !all_term, otp_code).await?;
-
-    if let Some(course_title) = course_title {
-        log::debug!("filtering courses by title: {course_title}");
-        courses.retain(|c| c.long_title().contains(course_title));
-    }
+    let mut courses = load_courses(ctx, force, !all_term, course, otp_code).await?;
 
     if let Some(course_id) = course_id {
         log::debug!("filtering courses by id: {course_id}");
@@ -140,32 +280,91 @@ pub async fn list(
         force,
         otp_code,
         opts.all_term,
-        opts.course_title.as_deref(),
+        opts.course.as_deref(),
         None,
     )
     .await?;
 
+    let path = opts.path.unwrap_or_default();
+
     let mut outbuf = Vec::new();
-    for (c, contents) in courses {
+    for (c, mut contents) in courses {
+        contents.retain(|ct| path.contains(ct.path()));
+        if contents.is_empty() {
+            continue;
+        }
+
         writeln!(outbuf, "{BL}{B}{}{B:#}{BL:#}", c.meta().title())?;
+        write_content_tree(&mut outbuf, &contents)?;
+        writeln!(outbuf)?;
+    }
 
-        for ct in &contents {
-            write!(
-                outbuf,
-                "{D}•{D:#} {MG}({:?}){MG:#} {}",
-                ct.kind(),
-                ct.title(),
-            )?;
-            if !ct.attachments().is_empty() {
-                write!(outbuf, " [{B}{}{B:#} 附件]", ct.attachments().len())?;
+    // write to stdout
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+
+    Ok(())
+}
+
+/// 按照文件夹层级输出课程内容。父节点不在 `contents` 中的条目按所在路径分组作为根节点。
+fn write_content_tree(buf: &mut Vec<u8>, contents: &[CourseContent]) -> std::io::Result<()> {
+    let ids = contents
+        .iter()
+        .map(|ct| ct.ccid().content_id().to_owned())
+        .collect::<std::collections::HashSet<_>>();
+
+    let mut groups: Vec<(String, Vec<&CourseContent>)> = Vec::new();
+    let mut children: HashMap<&str, Vec<&CourseContent>> = HashMap::new();
+    for ct in contents {
+        match ct.parent_id() {
+            Some(p) if ids.contains(p) => children.entry(p).or_default().push(ct),
+            _ => {
+                let key = ct.path().join("/");
+                match groups.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, v)) => v.push(ct),
+                    None => groups.push((key, vec![ct])),
+                }
             }
-            writeln!(outbuf, " {D}{}{D:#}", ct.ccid())?;
         }
     }
 
-    // write to stdout
-    buf_try!(@try fs::stdout().write_all(outbuf).await);
+    for (key, roots) in groups {
+        writeln!(buf, "{D}[{key}]{D:#}")?;
+        write_content_nodes(buf, &roots, &children, "")?;
+    }
+    Ok(())
+}
+
+fn write_content_nodes(
+    buf: &mut Vec<u8>,
+    nodes: &[&CourseContent],
+    children: &HashMap<&str, Vec<&CourseContent>>,
+    prefix: &str,
+) -> std::io::Result<()> {
+    for (i, ct) in nodes.iter().enumerate() {
+        let (branch, indent) = if i + 1 == nodes.len() {
+            ("└─", "   ")
+        } else {
+            ("├─", "│  ")
+        };
+        write!(
+            buf,
+            "{D}{prefix}{branch}{D:#} {MG}({:?}){MG:#} {}",
+            ct.kind(),
+            ct.title(),
+        )?;
+        if !ct.attachments().is_empty() {
+            write!(buf, " [{B}{}{B:#} 附件]", ct.attachments().len())?;
+        }
+        if !ct.embedded().is_empty() {
+            write!(buf, " [{B}{}{B:#} 内嵌资源]", ct.embedded().len())?;
+        }
+        let ccid = ct.ccid();
+        writeln!(buf, " {D}{ccid}{D:#}")?;
 
+        if let Some(sub) = children.get(ccid.content_id()) {
+            write_content_nodes(buf, sub, children, &format!("{prefix}{indent}"))?;
+        }
+    }
     Ok(())
 }
 
@@ -173,18 +372,24 @@ pub async fn list(
 pub struct DownloadOptions {
     /// 课程内容 ID（`course_id:content_id`，见 `pku3b cc list` 每行末尾）
     ccid: CourseContentID,
-    /// 文件下载目录 (支持相对路径)
+    /// 文件下载目录 (支持相对路径)，文件按课程内容所在的路径保存到其中的子目录
     #[arg(short = 'o', long)]
     outdir: Option<std::path::PathBuf>,
-    /// 将课程内容描述写入文本文件，不指定则不写入
+    /// 将课程内容描述写入 Markdown 文件（未指定扩展名时使用 `.md`），不指定则不写入
     #[arg(long)]
     output_desc: Option<String>,
     /// 将课程查询范围扩大到所有学期
     #[arg(long, default_value = "false")]
     all_term: bool,
-    /// 指定课程标题的子串
-    #[arg(long)]
-    course_title: Option<String>,
+    /// 只处理指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, alias = "course-title")]
+    course: Option<String>,
+    /// 目标文件已存在时的处理方式
+    #[arg(long, value_enum, default_value = "overwrite")]
+    on_conflict: OnConflict,
+    /// 同时下载的附件数
+    #[arg(short, long, default_value = "4")]
+    jobs: usize,
 }
 
 pub async fn download(
@@ -198,7 +403,7 @@ pub async fn download(
         force,
         otp_code,
         opts.all_term,
-        opts.course_title.as_deref(),
+        opts.course.as_deref(),
         Some(opts.ccid.course_id()),
     )
     .await?;
@@ -215,18 +420,24 @@ pub async fn download(
     log::debug!("{:?}", ct);
 
     let outdir = opts.outdir.unwrap_or_else(|| std::path::PathBuf::from("."));
+    let outdir = content_dir(&outdir, &ct);
     fs::create_dir_all(&outdir).await?;
 
     println!("Content kind: {:?}", ct.kind());
+    if !ct.path().is_empty() {
+        println!("Location: {}", ct.path().join("/"));
+    }
 
     if let Some(output_desc) = &opts.output_desc {
-        let dest = outdir.join(output_desc);
+        let mut dest = outdir.join(output_desc);
+        if dest.extension().is_none() {
+            dest.set_extension("md");
+        }
         println!("Writing description to {}", dest.display());
-        buf_try!(@try fs::write(dest, ct.descriptions().join("\n")).await);
+        buf_try!(@try fs::write(dest, ct.descriptions().join("\n\n") + "\n").await);
     }
 
     if matches!(ct.kind(), CourseContentKind::File) {
-        let dest = outdir.join(ct.title());
         let uri = c
             .client()
             .bb_course_content_file_uri(ct.ccid().course_id(), ct.ccid().content_id())
@@ -236,39 +447,39 @@ pub async fn download(
         let filename = percent_encoding::percent_decode(filename.as_bytes())
             .decode_utf8_lossy()
             .to_string();
-        println!("Downloading file {filename} to {}", dest.display());
-        let dest = outdir.join(&filename);
-        c.client()
-            .course_attachment_download(&uri, &dest, false)
+        println!("Downloading file {filename} to {}", outdir.display());
+        let target = DownloadTarget::Dir {
+            dir: &outdir,
+            name: &filename,
+            on_conflict: opts.on_conflict,
+            claimed: None,
+        };
+        let pb = ctx.multi.add(pbar::new_bytes(&filename));
+        let r = c
+            .client()
+            .course_attachment_download(&uri, target, false, pbar::bytes_progress(&pb))
             .await
             .with_context(|| format!("download attachment '{filename}'"))?;
+        pb.finish_and_clear();
+        ctx.multi.remove(&pb);
+        report_download(ctx, &filename, r.as_deref());
     }
 
-    let atts = ct.attachments();
+    let atts = ct
+        .attachments()
+        .iter()
+        .chain(ct.embedded())
+        .collect::<Vec<_>>();
     let tot = atts.len();
     if !atts.is_empty() {
-        println!("Downloading {} attachments to {}", tot, outdir.display());
-
-        let pb = ctx
-            .multi
-            .add(pbar::new(tot as u64))
-            .with_prefix("download")
-            .with_message(format!("[0/{tot}]"));
-        pb.tick();
-
-        for (i, (name, uri)) in atts.iter().enumerate() {
-            let dest = outdir.join(name);
-            pb.set_message(format!("[{}/{tot}] downloading '{name}'...", i + 1));
-            log::info!("downloading attachment {name} to {}", dest.display());
-            c.client()
-                .course_attachment_download(uri, &dest, true)
-                .await
-                .with_context(|| format!("download attachment '{name}'"))?;
-            pb.inc(1);
-        }
-
-        pb.finish_with_message("done.");
-        ctx.multi.remove(&pb);
+        println!(
+            "Downloading {} attachments ({} embedded) to {}",
+            tot,
+            ct.embedded().len(),
+            outdir.display()
+        );
+
+        download_attachments(ctx, c.client(), &atts, &outdir, opts.on_conflict, opts.jobs).await?;
     }
 
     Ok(())
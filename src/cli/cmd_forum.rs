@@ -0,0 +1,297 @@
+use anyhow::Context;
+
+use super::*;
+
+#[derive(clap::Args)]
+pub struct CommandForum {
+    /// 强制刷新
+    #[arg(short, long, default_value = "false")]
+    force: bool,
+
+    #[command(subcommand)]
+    command: ForumCommands,
+
+    /// 手机令牌码。当需要使用 OTP 登录，但未提供此参数时，将会从命令行交互式读取 OTP 码。
+    #[arg(long, default_value = "")]
+    otp_code: String,
+}
+
+#[derive(Subcommand)]
+enum ForumCommands {
+    /// 查看课程讨论板的论坛和主题帖 (含未读帖子数)
+    #[command(visible_alias("ls"))]
+    List {
+        /// 课程别名、课程 ID 或课程标题的子串/正则表达式
+        course: String,
+
+        /// 在所有学期的课程中查找
+        #[arg(long, default_value = "false")]
+        all_term: bool,
+    },
+
+    /// 查看主题帖及其回复 (查看后教学网会将其标记为已读)
+    Show {
+        /// 主题帖 ID (可通过 `pku3b forum list` 查看)
+        id: String,
+
+        #[command(flatten)]
+        scope: ThreadScope,
+    },
+
+    /// 回复主题帖
+    Reply {
+        /// 主题帖 ID (可通过 `pku3b forum list` 查看)
+        id: String,
+
+        /// 回复内容 (纯文本)，不指定则从命令行交互式读取
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// 从文件读取回复内容
+        #[arg(long, conflicts_with = "message")]
+        file: Option<std::path::PathBuf>,
+
+        /// 不经确认直接发布回复
+        #[arg(short, long, default_value = "false")]
+        yes: bool,
+
+        #[command(flatten)]
+        scope: ThreadScope,
+    },
+}
+
+/// 查找主题帖的范围
+#[derive(clap::Args)]
+struct ThreadScope {
+    /// 指定课程 (课程别名、课程 ID 或课程标题的子串/正则表达式)，缩小查找范围
+    #[arg(long, alias = "course-title")]
+    course: Option<String>,
+
+    /// 在所有学期的课程中查找
+    #[arg(long, default_value = "false")]
+    all_term: bool,
+}
+
+pub async fn run(cmd: CommandForum, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    match cmd.command {
+        ForumCommands::List { course, all_term } => {
+            list(ctx, cmd.force, &course, !all_term, cmd.otp_code).await?
+        }
+        ForumCommands::Show { id, scope } => show(ctx, cmd.force, &id, scope, cmd.otp_code).await?,
+        ForumCommands::Reply {
+            id,
+            message,
+            file,
+            yes,
+            scope,
+        } => {
+            let text = match (message, file) {
+                (Some(m), _) => m,
+                (None, Some(path)) => String::from_utf8(fs::read(&path).await?)
+                    .with_context(|| format!("read {}", path.display()))?,
+                (None, None) => inquire::Text::new("输入回复内容:").prompt()?,
+            };
+            anyhow::ensure!(!text.trim().is_empty(), "reply message is empty");
+            reply(ctx, &id, &text, yes, scope, cmd.otp_code).await?
+        }
+    }
+    Ok(())
+}
+
+type CourseForums = (Course, Vec<(CourseForumHandle, Vec<CourseThreadHandle>)>);
+
+async fn get_courses_forums(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    course: Option<&str>,
+    cur_term: bool,
+    otp_code: String,
+) -> anyhow::Result<Vec<CourseForums>> {
+    let courses = load_courses(ctx, force, cur_term, course, otp_code).await?;
+
+    let pb = ctx
+        .multi
+        .add(pbar::new(courses.len() as u64))
+        .with_prefix("All");
+    let futs = courses.into_iter().map(async |c| -> anyhow::Result<_> {
+        let c = c.get().await.context("fetch course")?;
+        let forums = c.get_forums().await.context("fetch forums")?;
+        let mut r = Vec::new();
+        for f in forums {
+            let threads = f
+                .get_threads()
+                .await
+                .with_context(|| format!("fetch threads of {}", f.meta().title()))?;
+            r.push((f, threads));
+        }
+        pb.inc(1);
+        Ok((c, r))
+    });
+    let courses = try_join_all(futs).await?;
+    pb.finish_and_clear();
+    ctx.multi.remove(&pb);
+
+    Ok(courses)
+}
+
+async fn find_thread(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    id: &str,
+    scope: ThreadScope,
+    otp_code: String,
+) -> anyhow::Result<(Course, CourseThreadHandle)> {
+    let courses = get_courses_forums(
+        ctx,
+        force,
+        scope.course.as_deref(),
+        !scope.all_term,
+        otp_code,
+    )
+    .await?;
+
+    courses
+        .into_iter()
+        .find_map(|(c, forums)| {
+            forums
+                .into_iter()
+                .flat_map(|(_, threads)| threads)
+                .find(|t| t.id() == id)
+                .map(|t| (c, t))
+        })
+        .with_context(|| format!("thread with id {id} not found"))
+}
+
+pub async fn list(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    course: &str,
+    cur_term: bool,
+    otp_code: String,
+) -> anyhow::Result<()> {
+    let courses = get_courses_forums(ctx, force, Some(course), cur_term, otp_code).await?;
+
+    let mut outbuf = Vec::new();
+    writeln!(outbuf, "{D}>{D:#} {B}讨论板{B:#} {D}<{D:#}\n")?;
+
+    for (c, forums) in courses {
+        writeln!(outbuf, "{BL}{H1}[{}]{H1:#}{BL:#}\n", c.meta().title())?;
+        if forums.is_empty() {
+            writeln!(outbuf, "{D}(无论坛){D:#}\n")?;
+            continue;
+        }
+
+        for (f, threads) in forums {
+            let m = f.meta();
+            write!(outbuf, "{B}{}{B:#}", m.title())?;
+            if let Some(total) = m.total() {
+                write!(outbuf, " {D}({total} 帖){D:#}")?;
+            }
+            if let Some(n) = m.unread().filter(|&n| n > 0) {
+                write!(outbuf, " {RD}{n} 未读{RD:#}")?;
+            }
+            writeln!(outbuf)?;
+            if !m.description().is_empty() {
+                writeln!(outbuf, "{D}{}{D:#}", m.description())?;
+            }
+
+            for t in threads {
+                let m = t.meta();
+                write!(outbuf, "{D}•{D:#} {}", m.title())?;
+                if !m.author().is_empty() {
+                    write!(outbuf, " {D}by{D:#} {}", m.author())?;
+                }
+                if !m.time().is_empty() {
+                    write!(outbuf, " {D}({}){D:#}", m.time())?;
+                }
+                if let Some(n) = m.unread().filter(|&n| n > 0) {
+                    write!(outbuf, " {RD}[{n} 未读]{RD:#}")?;
+                }
+                if let Some(total) = m.total() {
+                    write!(outbuf, " [{total} 帖]")?;
+                }
+                writeln!(outbuf, " {D}{}{D:#}", t.id())?;
+            }
+            writeln!(outbuf)?;
+        }
+    }
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+    Ok(())
+}
+
+async fn show(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    id: &str,
+    scope: ThreadScope,
+    otp_code: String,
+) -> anyhow::Result<()> {
+    let (c, t) = find_thread(ctx, force, id, scope, otp_code).await?;
+
+    let sp = ctx.spinner();
+    sp.set_message("fetching posts...");
+    let posts = t.get_posts().await.context("fetch posts")?;
+    ctx.remove_spinner(sp);
+
+    let mut outbuf = Vec::new();
+    writeln!(
+        outbuf,
+        "{BL}{B}{}{B:#}{BL:#} {D}>{D:#} {} {D}>{D:#} {BL}{B}{}{B:#}{BL:#}\n",
+        c.meta().name(),
+        t.forum().title(),
+        t.meta().title()
+    )?;
+    for (i, p) in posts.iter().enumerate() {
+        write!(outbuf, "{D}#{}{D:#} {B}{}{B:#}", i + 1, p.author())?;
+        if !p.time().is_empty() {
+            write!(outbuf, " {D}{}{D:#}", p.time())?;
+        }
+        writeln!(outbuf)?;
+        if i > 0 && !p.title().is_empty() && p.title() != t.meta().title() {
+            writeln!(outbuf, "{}", p.title())?;
+        }
+        for para in p.body() {
+            writeln!(outbuf, "{}", markdown::render_terminal(para))?;
+        }
+        writeln!(outbuf)?;
+    }
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+    Ok(())
+}
+
+async fn reply(
+    ctx: &CommandCtx<'_>,
+    id: &str,
+    text: &str,
+    yes: bool,
+    scope: ThreadScope,
+    otp_code: String,
+) -> anyhow::Result<()> {
+    let (c, t) = find_thread(ctx, false, id, scope, otp_code).await?;
+
+    if !yes {
+        println!(
+            "{B}{}{B:#} {D}>{D:#} {} {D}>{D:#} {B}{}{B:#}\n{text}",
+            c.meta().name(),
+            t.forum().title(),
+            t.meta().title()
+        );
+        let ok = inquire::Confirm::new("确认发布以上回复?")
+            .with_default(false)
+            .prompt()?;
+        if !ok {
+            println!("已取消");
+            return Ok(());
+        }
+    }
+
+    let sp = ctx.spinner();
+    sp.set_message(format!("replying to '{}'...", t.meta().title()));
+    t.reply(text).await.context("reply thread")?;
+    ctx.remove_spinner(sp);
+
+    println!("{GR}已回复{GR:#} {}", t.meta().title());
+    Ok(())
+}
@@ -6,6 +6,16 @@ pub struct CommandGrades {
     force: bool,
     #[arg(long, default_value = "false")]
     all_term: bool,
+    /// 只查询指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long)]
+    course: Option<String>,
+    /// 只输出与上次查询相比新发布或被修改的成绩 (每行一个 JSON 对象)，便于脚本使用
+    #[arg(long, default_value = "false")]
+    changes: bool,
+    /// 与 `--changes` 一起使用：输出成绩历史中在该时间之后记录的所有变动
+    /// (包括之前查询时发现的)，时间格式同输出中的 `time` 字段
+    #[arg(long, requires = "changes")]
+    since: Option<chrono::DateTime<chrono::FixedOffset>>,
     #[arg(long, default_value = "")]
     otp_code: String,
 }
@@ -24,33 +34,156 @@ pub async fn run(cmd: CommandGrades, ctx: &CommandCtx<'_>) -> anyhow::Result<()>
     let mut enrollments = b.user_courses(&user_id).await?;
     enrollments.retain(|e| e.course_role_id == "Student");
 
-    let mut all_grades = Vec::new();
-    let total = enrollments.len();
+    if let Some(course) = &cmd.course {
+        let sel = course_selector(ctx, course).await?;
+        let ids = b
+            .get_courses(false)
+            .await?
+            .into_iter()
+            .filter(|c| c.meta().matches(&sel))
+            .map(|c| c.id().to_owned())
+            .collect::<Vec<_>>();
+        enrollments.retain(|e| ids.contains(&e.course_id));
+        anyhow::ensure!(!enrollments.is_empty(), "no course matches '{course}'");
+    }
+
+    sp.finish_with_message("done.");
+    ctx.remove_spinner(sp);
 
-    for (i, enrollment) in enrollments.iter().enumerate() {
-        sp.set_message(format!("fetching grades {}/{}...", i + 1, total));
+    // 同时获取的课程数量，每门课程内部还会并发获取各成绩项
+    const COURSE_CONCURRENCY: usize = 4;
 
-        let detail = match b.course_detail(&enrollment.course_id).await {
-            Ok(d) => d,
-            Err(e) => {
-                log::error!("error fetching course detail: {e}");
-                continue;
+    let pb = ctx
+        .multi
+        .add(pbar::new(enrollments.len() as u64))
+        .with_prefix("All");
+    let grades = futures_util::stream::iter(&enrollments)
+        .map(async |enrollment| -> anyhow::Result<_> {
+            let detail = match b.course_detail(&enrollment.course_id).await {
+                Ok(d) => d,
+                Err(e) => {
+                    log::error!("error fetching course detail: {e}");
+                    pb.inc(1);
+                    return Ok(Vec::new());
+                }
+            };
+
+            if !detail.data().is_available() {
+                pb.inc(1);
+                return Ok(Vec::new());
             }
-        };
 
-        if !detail.data().is_available() {
-            continue;
+            let cpb = ctx
+                .multi
+                .add(pbar::new(0).with_prefix(detail.data().name().to_owned()));
+            let grades = detail
+                .all_grades(|n, len| {
+                    cpb.set_length(len);
+                    cpb.set_position(n);
+                })
+                .await
+                .with_context(|| format!("fetch grades of {}", detail.data().name()))?;
+            cpb.finish_with_message("done.");
+            pb.inc(1);
+            Ok(grades)
+        })
+        .buffered(COURSE_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    ctx.multi.remove(&pb);
+
+    let mut all_grades = Vec::new();
+    for r in grades {
+        all_grades.extend(r?);
+    }
+
+    let mut history = grade::GradeHistory::load()
+        .await
+        .context("load grade history")?;
+    let changes = history.update(&all_grades, chrono::Local::now());
+    history.save().await.context("save grade history")?;
+
+    if cmd.changes {
+        let changes = match cmd.since {
+            Some(since) => history.changes_since(since).collect(),
+            None => changes.iter().collect::<Vec<_>>(),
+        };
+        let mut outbuf = Vec::new();
+        for c in changes {
+            serde_json::to_writer(&mut outbuf, c)?;
+            writeln!(outbuf)?;
         }
+        buf_try!(@try fs::stdout().write_all(outbuf).await);
+        return Ok(());
+    }
+    print_changes(&changes).await?;
+
+    let cfg = config::read_cfg(&ctx.config_path)
+        .await
+        .context("read config file")?;
+    print_grades(&all_grades, &cfg.grades.unwrap_or_default(), cmd.all_term).await?;
+    Ok(())
+}
 
-        all_grades.extend(detail.all_grades().await?);
+async fn print_changes(changes: &[grade::GradeChange]) -> anyhow::Result<()> {
+    if changes.is_empty() {
+        return Ok(());
     }
 
-    sp.finish_with_message("done.");
-    print_grades(&all_grades, cmd.all_term).await?;
+    let mut outbuf = Vec::new();
+    writeln!(outbuf, "{D}>{D:#} {B}成绩变动{B:#} {D}<{D:#}\n")?;
+    for c in changes {
+        match c.kind {
+            grade::ChangeKind::New => write!(outbuf, "{GR}new{GR:#}")?,
+            grade::ChangeKind::Modified => write!(outbuf, "{MG}modified{MG:#}")?,
+        }
+        write!(outbuf, ": {} {D}({}){D:#} ", c.column_name, c.course_name)?;
+        if let Some(status) = c.old_status {
+            let old = format_grade(c.old_score, c.old_text.as_deref(), status);
+            write!(outbuf, "{D}{old} →{D:#} ")?;
+        }
+        write!(
+            outbuf,
+            "{B}{}{B:#}",
+            format_grade(c.score, c.text.as_deref(), c.status)
+        )?;
+        if c.possible > 0.0 && c.status != GradeStatus::Exempt {
+            write!(outbuf, "/{:.0}", c.possible)?;
+        }
+        writeln!(outbuf)?;
+    }
+    writeln!(outbuf)?;
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
     Ok(())
 }
 
-async fn print_grades(grades: &[GradeRecord], all_term: bool) -> anyhow::Result<()> {
+/// 成绩的显示文本：等级等非数值成绩优先，其次是分数和批改状态
+fn format_grade(score: Option<f64>, text: Option<&str>, status: GradeStatus) -> String {
+    match (text, score, status) {
+        (_, _, GradeStatus::Exempt) => "免除".to_owned(),
+        (Some(t), Some(s), _) => format!("{t} ({s:.1})"),
+        (Some(t), None, _) => t.to_owned(),
+        (None, Some(s), _) => format!("{s:.1}"),
+        (None, None, GradeStatus::NeedsGrading) => "待批改".to_owned(),
+        (None, None, _) => "--".to_owned(),
+    }
+}
+
+/// 是否在成绩列表中显示该成绩项
+fn shown(item: &GradeRecord) -> bool {
+    item.visible
+        && (item.score.is_some()
+            || item.text.is_some()
+            || matches!(item.status, GradeStatus::NeedsGrading | GradeStatus::Exempt))
+}
+
+async fn print_grades(
+    grades: &[GradeRecord],
+    cfgs: &[config::GradeCourseConfig],
+    all_term: bool,
+) -> anyhow::Result<()> {
     if grades.is_empty() {
         println!("暂无成绩数据");
         return Ok(());
@@ -67,9 +200,6 @@ async fn print_grades(grades: &[GradeRecord], all_term: bool) -> anyhow::Result<
     let mut course_map: std::collections::HashMap<&str, Vec<&GradeRecord>> =
         std::collections::HashMap::new();
     for g in grades {
-        if !all_term && extract_term(&g.course_name) != latest_term {
-            continue;
-        }
         if !course_map.contains_key(g.course_name.as_str()) {
             courses.push(&g.course_name);
         }
@@ -79,36 +209,118 @@ async fn print_grades(grades: &[GradeRecord], all_term: bool) -> anyhow::Result<
             .push(g);
     }
 
+    // 各课程的总评和学分，用于计算学期和累计绩点
+    let totals = courses
+        .iter()
+        .filter_map(|&name| {
+            let cfg = grade::find_config(cfgs, name);
+            let total = grade::course_total(&course_map[name], cfg)?;
+            let credit = cfg.and_then(|c| c.credit).unwrap_or(1.0);
+            Some((name, (total, credit)))
+        })
+        .collect::<std::collections::HashMap<_, _>>();
+
     let mut outbuf = Vec::new();
     let mut displayed = 0usize;
+    let mut terms: Vec<&str> = Vec::new();
 
     for course_name in &courses {
+        let term = extract_term(course_name);
+        if !all_term && term != latest_term {
+            continue;
+        }
         let items = &course_map[*course_name];
-        if items.iter().all(|item| item.score.is_none()) {
+        if !items.iter().any(|item| shown(item)) {
             continue;
         }
         if displayed == 0 {
             writeln!(outbuf, "{D}>{D:#} {B}成绩查询{B:#} {D}<{D:#}\n")?;
         }
         displayed += 1;
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
         writeln!(outbuf, "{BL}{B}{course_name}{B:#}{BL:#}")?;
         for item in items {
-            if item.score.is_none() {
+            // 总计列体现在下方的总评中
+            if !shown(item) || (item.calculated && !item.column_name.contains("平时")) {
                 continue;
             }
             write!(outbuf, "{D}*{D:#} {} ", item.column_name)?;
-            match item.score {
-                Some(s) => write!(outbuf, "{GR}{:.1}{GR:#}", s)?,
-                None => write!(outbuf, "{D}--{D:#}")?,
+            if let Some(c) = &item.category {
+                write!(outbuf, "{D}[{c}]{D:#} ")?;
+            }
+            match (&item.text, item.score, item.status) {
+                (_, _, GradeStatus::Exempt) => write!(outbuf, "{D}免除{D:#}")?,
+                (Some(t), Some(s), _) => write!(outbuf, "{GR}{t}{GR:#} {D}({s:.1}){D:#}")?,
+                (Some(t), None, _) => write!(outbuf, "{GR}{t}{GR:#}")?,
+                (None, Some(s), _) => write!(outbuf, "{GR}{:.1}{GR:#}", s)?,
+                (None, None, GradeStatus::NeedsGrading) => write!(outbuf, "{MG}待批改{MG:#}")?,
+                (None, None, _) => write!(outbuf, "{D}--{D:#}")?,
             };
-            if item.possible > 0.0 {
+            if item.possible > 0.0 && item.status != GradeStatus::Exempt {
                 write!(outbuf, "{D} / {:.0}{D:#}", item.possible)?;
             }
+            if item.status == GradeStatus::NeedsGrading
+                && (item.score.is_some() || item.text.is_some())
+            {
+                write!(outbuf, " {MG}(待批改){MG:#}")?;
+            }
+            if let Some(due) = item.due.as_deref().and_then(Deadline::parse) {
+                write!(
+                    outbuf,
+                    " {D}截止 {}{D:#}",
+                    due.time()
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                )?;
+            }
+            writeln!(outbuf)?;
+            if let Some(desc) = item.description.as_deref().and_then(|d| d.lines().next()) {
+                writeln!(outbuf, "  {D}{desc}{D:#}")?;
+            }
+        }
+        if let Some((total, _)) = totals.get(course_name) {
+            write!(
+                outbuf,
+                "{D}*{D:#} {B}总评{B:#} {GR}{:.1}{GR:#}{D} / {:.0}{D:#}",
+                total.score, total.possible
+            )?;
+            if total.possible != 100.0 {
+                write!(outbuf, " ({:.1}%)", total.percent())?;
+            }
+            write!(outbuf, " {D}绩点{D:#} {MG}{:.2}{MG:#}", total.gpa())?;
+            match &total.source {
+                grade::TotalSource::Weighted => write!(outbuf, " {D}(自定义权重){D:#}")?,
+                grade::TotalSource::Calculated(name) => write!(outbuf, " {D}({name}){D:#}")?,
+                grade::TotalSource::Sum => write!(outbuf, " {D}(已评分项之和){D:#}")?,
+            }
             writeln!(outbuf)?;
         }
         writeln!(outbuf)?;
     }
 
+    if displayed > 0 {
+        for term in terms {
+            let gpa = grade::average_gpa(
+                totals
+                    .iter()
+                    .filter(|(name, _)| extract_term(name) == term)
+                    .map(|(_, (t, credit))| (t, *credit)),
+            );
+            if let Some(gpa) = gpa {
+                writeln!(outbuf, "{B}学期绩点{B:#} {term} {MG}{gpa:.2}{MG:#}")?;
+            }
+        }
+        if let Some(gpa) = grade::average_gpa(totals.values().map(|(t, credit)| (t, *credit))) {
+            writeln!(outbuf, "{B}累计绩点{B:#} {MG}{gpa:.2}{MG:#}")?;
+        }
+        writeln!(
+            outbuf,
+            "{D}绩点按当前已评分项估算，未配置学分的课程按 1 学分计{D:#}"
+        )?;
+    }
+
     if displayed == 0 {
         println!("暂无成绩数据");
     } else {
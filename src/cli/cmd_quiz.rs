@@ -0,0 +1,136 @@
+use super::*;
+
+#[derive(clap::Args)]
+pub struct CommandQuiz {
+    /// 强制刷新
+    #[arg(short, long, default_value = "false")]
+    force: bool,
+
+    /// 只处理指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, global = true)]
+    course: Option<String>,
+
+    #[command(subcommand)]
+    command: QuizCommands,
+
+    /// 手机令牌码。当需要使用 OTP 登录，但未提供此参数时，将会从命令行交互式读取 OTP 码。
+    #[arg(long, default_value = "")]
+    otp_code: String,
+}
+
+#[derive(Subcommand)]
+enum QuizCommands {
+    /// 查看测验列表（开放时间、截止时间、时间限制、尝试次数与成绩），按照截止日期排序
+    #[command(visible_alias("ls"))]
+    List {
+        /// 显示所有学期的测验
+        #[arg(long, default_value = "false")]
+        all_term: bool,
+    },
+}
+
+pub async fn run(cmd: CommandQuiz, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    match cmd.command {
+        QuizCommands::List { all_term } => {
+            list(
+                ctx,
+                cmd.force,
+                !all_term,
+                cmd.course.as_deref(),
+                cmd.otp_code,
+            )
+            .await?
+        }
+    }
+    Ok(())
+}
+
+pub async fn list(
+    ctx: &CommandCtx<'_>,
+    force: bool,
+    cur_term: bool,
+    course: Option<&str>,
+    otp_code: String,
+) -> anyhow::Result<()> {
+    let courses =
+        cmd_assignment::get_courses_and_assignments(ctx, force, cur_term, course, true, otp_code)
+            .await?;
+
+    let mut quizzes = courses
+        .iter()
+        .flat_map(|(c, _, quizzes)| quizzes.iter().map(move |(id, q)| (c, id, q)))
+        .collect::<Vec<_>>();
+    quizzes.sort_by_cached_key(|(_, _, q)| q.deadline());
+
+    let mut outbuf = Vec::new();
+    let total = quizzes.len();
+    writeln!(outbuf, "{D}>{D:#} {B}测验 ({total}){B:#} {D}<{D:#}\n")?;
+    for (c, id, q) in quizzes {
+        write_course_quiz(&mut outbuf, id, c, q)?;
+    }
+
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+    Ok(())
+}
+
+fn fmt_time(d: &Deadline) -> String {
+    d.local().format("%Y-%m-%d %H:%M").to_string()
+}
+
+pub fn write_course_quiz(
+    buf: &mut Vec<u8>,
+    id: &str,
+    c: &Course,
+    q: &CourseQuiz,
+) -> std::io::Result<()> {
+    write!(buf, "{BL}{B}{}{B:#}{BL:#} {D}>{D:#} ", c.meta().name())?;
+    write!(buf, "{MG}[测验]{MG:#} {BL}{B}{}{B:#}{BL:#}", q.title())?;
+    if let Some((score, _)) = q.score() {
+        write!(buf, " ({GR}已评分: {score}{GR:#})")?;
+    } else if q.is_attempted() {
+        write!(buf, " ({GR}已作答{GR:#})")?;
+    } else if let Some(t) = q.deadline() {
+        write!(buf, " ({})", cmd_assignment::fmt_time_delta(t.remaining()))?;
+    } else {
+        write!(buf, " (无截止时间)")?;
+    }
+    writeln!(buf, " {D}{id}{D:#}")?;
+
+    let (from, until) = (q.available_from(), q.available_until());
+    if from.is_some() || until.is_some() {
+        writeln!(
+            buf,
+            "{D}开放时间:{D:#} {} ~ {}",
+            from.as_ref().map(fmt_time).unwrap_or_default(),
+            until.as_ref().map(fmt_time).unwrap_or_default(),
+        )?;
+    }
+    if let Some(d) = q.deadline() {
+        writeln!(buf, "{D}截止时间:{D:#} {}", fmt_time(&d))?;
+    }
+    if let Some(t) = q.time_limit() {
+        writeln!(buf, "{D}时间限制:{D:#} {t}")?;
+    }
+    match (q.attempts_used(), q.attempts_allowed()) {
+        (used, Some(0)) => writeln!(buf, "{D}尝试次数:{D:#} {} / 不限", used.unwrap_or(0))?,
+        (used, Some(n)) => writeln!(buf, "{D}尝试次数:{D:#} {} / {n}", used.unwrap_or(0))?,
+        (Some(used), None) => writeln!(buf, "{D}尝试次数:{D:#} {used}")?,
+        (None, None) => {}
+    }
+    if let Some((score, possible)) = q.score() {
+        write!(buf, "{D}成绩:{D:#} {GR}{score:.1}{GR:#}")?;
+        if let Some(p) = possible {
+            write!(buf, "{D} / {p:.0}{D:#}")?;
+        }
+        writeln!(buf)?;
+    }
+    if !q.descriptions().is_empty() {
+        writeln!(buf)?;
+        for p in q.descriptions() {
+            writeln!(buf, "{}", markdown::render_terminal(p))?;
+        }
+    }
+    writeln!(buf)?;
+
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Context;
+
+use super::*;
+
+/// 同步记录文件名，位于同步目录的根目录下
+const MANIFEST: &str = ".pku3b-sync.json";
+
+#[derive(clap::Args)]
+pub struct CommandSync {
+    /// 同步目录 (支持相对路径)，课程内容保存为 `<dir>/<课程>/<菜单项>/<文件夹>/...`
+    dir: std::path::PathBuf,
+
+    /// 强制刷新
+    #[arg(short, long, default_value = "false")]
+    force: bool,
+
+    /// 只同步指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, alias = "course-title")]
+    course: Option<String>,
+
+    /// 手机令牌码。当需要使用 OTP 登录，但未提供此参数时，将会从命令行交互式读取 OTP 码。
+    #[arg(long, default_value = "")]
+    otp_code: String,
+}
+
+/// 同步记录，以文件相对于同步目录的路径为键
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Manifest {
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ManifestEntry {
+    /// 课程内容 ID (`course_id:content_id`)
+    ccid: String,
+    /// 文件重定向后的真实地址，教学网上的文件被替换后会改变
+    remote: String,
+    size: u64,
+    /// 远端文件的 `Last-Modified`，服务器未提供时为 `None`
+    #[serde(default)]
+    modified: Option<String>,
+}
+
+impl Manifest {
+    async fn load(dir: &std::path::Path) -> anyhow::Result<Self> {
+        let path = dir.join(MANIFEST);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let buf = fs::read(&path).await?;
+        serde_json::from_slice(&buf).with_context(|| format!("parse {}", path.display()))
+    }
+
+    async fn save(&self, dir: &std::path::Path) -> anyhow::Result<()> {
+        let buf = serde_json::to_vec_pretty(self)?;
+        buf_try!(@try fs::write(dir.join(MANIFEST), buf).await);
+        Ok(())
+    }
+
+    /// 本地文件存在且与记录的远端地址、修改时间和大小一致时无需重新下载
+    fn is_fresh(
+        &self,
+        dir: &std::path::Path,
+        rel: &str,
+        remote: &str,
+        modified: Option<&str>,
+    ) -> bool {
+        let Some(e) = self.files.get(rel) else {
+            return false;
+        };
+        e.remote == remote
+            && e.modified.as_deref() == modified
+            && std::fs::metadata(dir.join(rel)).is_ok_and(|m| m.is_file() && m.len() == e.size)
+    }
+}
+
+pub async fn run(cmd: CommandSync, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    let dir = cmd.dir;
+    fs::create_dir_all(&dir).await?;
+
+    let courses = cmd_course_content::get_courses_contents(
+        ctx,
+        cmd.force,
+        cmd.otp_code,
+        false,
+        cmd.course.as_deref(),
+        None,
+    )
+    .await?;
+
+    let mut manifest = Manifest::load(&dir).await?;
+    let mut seen = HashSet::new();
+    let mut failures = Vec::new();
+    let (mut downloaded, mut skipped) = (0usize, 0usize);
+    let mut synced_courses = HashSet::new();
+    // 获取文件列表失败的课程不检查远端删除，避免误删同步记录
+    let mut failed_courses = HashSet::new();
+
+    for (c, contents) in courses {
+        let course_dir = sanitize_filename(c.meta().name());
+        synced_courses.insert(course_dir.clone());
+
+        let pb = ctx
+            .multi
+            .add(pbar::new(contents.len() as u64))
+            .with_prefix(c.meta().name().to_owned());
+        pb.tick();
+
+        for ct in &contents {
+            pb.inc(1);
+
+            // collect (file name, resolved remote location) of this content
+            let mut files = Vec::new();
+            if matches!(ct.kind(), CourseContentKind::File) {
+                let ccid = ct.ccid();
+                match c
+                    .client()
+                    .bb_course_content_file_uri(ccid.course_id(), ccid.content_id())
+                    .await
+                {
+                    Ok(loc) => {
+                        let filename = loc.rsplit_once('/').map_or(loc.as_str(), |(_, s)| s);
+                        let filename = percent_encoding::percent_decode(filename.as_bytes())
+                            .decode_utf8_lossy()
+                            .to_string();
+                        files.push((filename, loc));
+                    }
+                    Err(e) => {
+                        failed_courses.insert(course_dir.clone());
+                        failures.push((ct.title().to_owned(), e));
+                    }
+                }
+            }
+            for (name, uri) in ct.attachments() {
+                match c.client().course_attachment_location(uri).await {
+                    Ok(loc) => files.push((name.to_owned(), loc)),
+                    Err(e) => {
+                        failed_courses.insert(course_dir.clone());
+                        failures.push((name.to_owned(), e));
+                    }
+                }
+            }
+
+            let rel = content_dir(std::path::Path::new(&course_dir), ct);
+
+            for (name, loc) in files {
+                // 不同的文件对应同一路径时加上序号
+                let base = rel.join(sanitize_filename(&name));
+                let (rel, key) = std::iter::once(base.clone())
+                    .chain((1..).map(|i| numbered(&base, i)))
+                    .map(|p| {
+                        let key = p.to_string_lossy().replace('\\', "/");
+                        (p, key)
+                    })
+                    .find(|(_, key)| !seen.contains(key))
+                    .unwrap();
+                seen.insert(key.clone());
+                let modified = match c.client().course_attachment_modified(&loc).await {
+                    Ok(m) => m,
+                    Err(e) => {
+                        failed_courses.insert(course_dir.clone());
+                        failures.push((name, e));
+                        continue;
+                    }
+                };
+                if manifest.is_fresh(&dir, &key, &loc, modified.as_deref()) {
+                    skipped += 1;
+                    continue;
+                }
+
+                pb.set_message(format!("downloading '{name}'..."));
+                let dest = dir.join(&rel);
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                let fpb = ctx.multi.add(pbar::new_bytes(&name));
+                let r = c
+                    .client()
+                    .course_attachment_download(
+                        &loc,
+                        DownloadTarget::Path(&dest),
+                        false,
+                        pbar::bytes_progress(&fpb),
+                    )
+                    .await
+                    .with_context(|| format!("download '{key}'"));
+                fpb.finish_and_clear();
+                ctx.multi.remove(&fpb);
+                if let Err(e) = r {
+                    failures.push((name, e));
+                    continue;
+                }
+
+                let size = std::fs::metadata(&dest)?.len();
+                ctx.multi.println(format!("{GR}+{GR:#} {key}"))?;
+                manifest.files.insert(
+                    key,
+                    ManifestEntry {
+                        ccid: ct.ccid().to_string(),
+                        remote: loc,
+                        size,
+                        modified,
+                    },
+                );
+                downloaded += 1;
+            }
+        }
+
+        pb.finish_and_clear();
+        ctx.multi.remove(&pb);
+        manifest.save(&dir).await?;
+    }
+
+    // files recorded before but no longer on blackboard (only for the courses synced this time
+    // and fully listed without errors)
+    let deleted = manifest
+        .files
+        .keys()
+        .filter(|k| !seen.contains(*k))
+        .filter(|k| {
+            k.split_once('/').is_some_and(|(course, _)| {
+                synced_courses.contains(course) && !failed_courses.contains(course)
+            })
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    for k in &deleted {
+        manifest.files.remove(k);
+    }
+    manifest.save(&dir).await?;
+
+    let mut outbuf = Vec::new();
+    for k in &deleted {
+        writeln!(
+            outbuf,
+            "{RD}-{RD:#} {k} {D}(已从教学网删除，本地文件保留){D:#}"
+        )?;
+    }
+    writeln!(
+        outbuf,
+        "同步完成: 下载 {B}{downloaded}{B:#} 个文件, 跳过 {B}{skipped}{B:#} 个未变化的文件, 远端删除 {B}{}{B:#} 个文件",
+        deleted.len()
+    )?;
+    for (name, e) in &failures {
+        writeln!(outbuf, "{RD}失败{RD:#} {name}: {e:#}")?;
+    }
+    buf_try!(@try fs::stdout().write_all(outbuf).await);
+
+    anyhow::ensure!(
+        failures.is_empty(),
+        "{} files failed to sync",
+        failures.len()
+    );
+    Ok(())
+}
@@ -8,6 +8,10 @@ pub struct CommandVideo {
     #[arg(short, long, default_value = "false")]
     force: bool,
 
+    /// 只处理指定的课程：课程别名、课程 ID 或课程标题的子串/正则表达式
+    #[arg(long, global = true)]
+    course: Option<String>,
+
     #[command(subcommand)]
     command: VideoCommands,
 
@@ -29,38 +33,37 @@ enum VideoCommands {
     /// 下载课程回放视频 (MP4 格式)，支持断点续传
     #[command(visible_alias("down"))]
     #[cfg(feature = "video-download")]
-    Download {
-        /// 课程回放 ID (形如 `e780808c9eb81f61`, 可通过 `pku3b video list` 查看)
-        id: String,
+    Download(DownloadOptions),
+}
 
-        /// 在所有学期的课程回放范围中查找
-        #[arg(long, default_value = "false")]
-        all_term: bool,
+#[derive(clap::Args)]
+#[cfg(feature = "video-download")]
+struct DownloadOptions {
+    /// 课程回放 ID (形如 `e780808c9eb81f61`, 可通过 `pku3b video list` 查看)
+    id: String,
 
-        /// 文件下载目录 (支持相对路径)
-        #[arg(short = 'o', long)]
-        outdir: Option<std::path::PathBuf>,
-    },
+    /// 在所有学期的课程回放范围中查找
+    #[arg(long, default_value = "false")]
+    all_term: bool,
+
+    /// 文件下载目录 (支持相对路径)
+    #[arg(short = 'o', long)]
+    outdir: Option<std::path::PathBuf>,
+
+    /// 目标文件已存在时的处理方式
+    #[arg(long, value_enum, default_value = "overwrite")]
+    on_conflict: OnConflict,
 }
 
 pub async fn run(cmd: CommandVideo, ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
+    let course = cmd.course.as_deref();
     match cmd.command {
-        VideoCommands::List { all_term } => list(ctx, cmd.force, !all_term, cmd.otp_code).await?,
+        VideoCommands::List { all_term } => {
+            list(ctx, cmd.force, !all_term, course, cmd.otp_code).await?
+        }
         #[cfg(feature = "video-download")]
-        VideoCommands::Download {
-            outdir,
-            id,
-            all_term,
-        } => {
-            download(
-                ctx,
-                outdir.as_deref(),
-                cmd.force,
-                id,
-                !all_term,
-                cmd.otp_code,
-            )
-            .await?
+        VideoCommands::Download(opts) => {
+            download(ctx, cmd.force, course, cmd.otp_code, opts).await?
         }
     }
     Ok(())
@@ -70,9 +73,10 @@ pub async fn list(
     ctx: &CommandCtx<'_>,
     force: bool,
     cur_term: bool,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<()> {
-    let courses = load_courses(ctx, force, cur_term, otp_code).await?;
+    let courses = load_courses(ctx, force, cur_term, course, otp_code).await?;
 
     let pb = ctx
         .multi
@@ -118,20 +122,20 @@ pub async fn list(
 }
 
 #[cfg(feature = "video-download")]
-pub async fn download(
+async fn download(
     ctx: &CommandCtx<'_>,
-    outdir: Option<&std::path::Path>,
     force: bool,
-    id: String,
-    cur_term: bool,
+    course: Option<&str>,
     otp_code: String,
+    opts: DownloadOptions,
 ) -> anyhow::Result<()> {
-    let outdir = outdir.unwrap_or(std::path::Path::new("."));
+    let (id, cur_term) = (opts.id, !opts.all_term);
+    let outdir = opts.outdir.as_deref().unwrap_or(std::path::Path::new("."));
     if !outdir.exists() {
         anyhow::bail!("output directory {:?} not exists", outdir.display());
     }
 
-    let (_, courses, sp) = load_client_courses(ctx, force, cur_term, otp_code).await?;
+    let (_, courses, sp) = load_client_courses(ctx, force, cur_term, course, otp_code).await?;
 
     sp.set_message("finding video...");
     let mut target_video = None;
@@ -161,6 +165,13 @@ pub async fn download(
 
     println!("下载课程回放：{} ({})", v.course_name(), v.meta().title());
 
+    let name = format!("{}_{}.mp4", v.course_name(), v.meta().title());
+    let dest = outdir.join(sanitize_filename(&name));
+    let Some(dest) = opts.on_conflict.resolve(&dest) else {
+        println!("文件已存在，跳过: {}", dest.display());
+        return Ok(());
+    };
+
     // prepare download dir
     let dir = utils::cache_dir().join("video_download").join(&id);
     fs::create_dir_all(&dir)
@@ -178,8 +189,6 @@ pub async fn download(
     let merged = dir.join("merged").with_extension("ts");
     merge_segments(ctx, &merged, &paths).await?;
 
-    let dest = format!("{}_{}.mp4", v.course_name(), v.meta().title());
-    let dest = outdir.join(&dest);
     log::info!("Merged segments to {}", merged.display());
     log::info!(
         r#"You may execute `ffmpeg -i "{}" -c copy "{}"` to convert it to mp4"#,
@@ -187,7 +196,7 @@ pub async fn download(
         dest.display(),
     );
 
-    // convert the merged ts file to mp4. overwrite existing file
+    // convert the merged ts file to mp4. overwrite existing file (see `--on-conflict`)
     let sp = ctx.spinner();
     sp.set_message("Converting to mp4 file...");
     let c = compio::process::Command::new("ffmpeg")
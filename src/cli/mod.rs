@@ -2,10 +2,15 @@ mod cmd_announcement;
 mod cmd_assignment;
 #[cfg(feature = "bark")]
 mod cmd_bark;
+mod cmd_calendar;
+mod cmd_course;
 mod cmd_course_content;
 mod cmd_course_table;
+mod cmd_forum;
 mod cmd_grades;
+mod cmd_quiz;
 mod cmd_syllabus;
+mod cmd_sync;
 #[cfg(feature = "thesislib")]
 mod cmd_thesis_lib;
 #[cfg(feature = "ttshitu")]
@@ -30,7 +35,7 @@ impl CommandCtx<'_> {
 
 use crate::api::{blackboard::*, syllabus::*};
 use crate::cli::pbar::AsyncSpinner;
-use crate::{api, build, config, utils, walkdir};
+use crate::{api, build, config, grade, markdown, mime, utils, walkdir};
 use anyhow::Context as _;
 use clap::{
     CommandFactory, Parser, Subcommand,
@@ -77,10 +82,21 @@ enum Commands {
     #[command(visible_alias("a"), arg_required_else_help(true))]
     Assignment(cmd_assignment::CommandAssignment),
 
+    /// 查看课程测验
+    #[command(visible_alias("q"), arg_required_else_help(true))]
+    Quiz(cmd_quiz::CommandQuiz),
+
     /// 获取课程内容
     #[command(visible_alias("cc"))]
     CourseContent(cmd_course_content::CommandCourseContent),
 
+    /// 查看课程信息 (教师、助教、上课时间、课程简介)
+    #[command(arg_required_else_help(true))]
+    Course(cmd_course::CommandCourse),
+
+    /// 将当前学期所有课程的课程内容增量同步到本地目录
+    Sync(cmd_sync::CommandSync),
+
     /// 获取个人课表
     #[command(name = "coursetable", visible_alias("ct"))]
     CourseTable(cmd_course_table::CommandCourseTable),
@@ -93,6 +109,14 @@ enum Commands {
     )]
     Announcement(cmd_announcement::CommandAnnouncement),
 
+    /// 查看日历事件及作业截止时间
+    #[command(visible_alias("cal"), arg_required_else_help(true))]
+    Calendar(cmd_calendar::CommandCalendar),
+
+    /// 查看课程讨论板/回复主题帖
+    #[command(arg_required_else_help(true))]
+    Forum(cmd_forum::CommandForum),
+
     /// 获取课程回放/下载课程回放
     #[command(visible_alias("v"), arg_required_else_help(true))]
     Video(cmd_video::CommandVideo),
@@ -163,6 +187,172 @@ impl clap::ValueEnum for DualDegree {
     }
 }
 
+impl clap::ValueEnum for OnConflict {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Skip, Self::Overwrite, Self::Rename]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            Self::Skip => Some(clap::builder::PossibleValue::new("skip").help("跳过")),
+            Self::Overwrite => Some(clap::builder::PossibleValue::new("overwrite").help("覆盖")),
+            Self::Rename => {
+                Some(clap::builder::PossibleValue::new("rename").help("在文件名后加上序号"))
+            }
+        }
+    }
+}
+
+/// 时间范围的端点：相对时间 (`3d`、`12h`、`2w` 表示之前，`+3d` 表示之后)、日期或具体时间
+#[derive(Debug, Clone)]
+enum TimeBound {
+    Instant(chrono::DateTime<chrono::FixedOffset>),
+    Day(chrono::NaiveDate),
+}
+
+impl std::str::FromStr for TimeBound {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (future, rel) = match s.strip_prefix('+') {
+            Some(rel) => (true, rel),
+            None => (false, s),
+        };
+        if let Some((n, unit)) = rel
+            .find(|c: char| !c.is_ascii_digit())
+            .filter(|&i| i > 0)
+            .map(|i| rel.split_at(i))
+        {
+            let n = n.parse::<i64>()?;
+            let delta = match unit {
+                "m" | "min" => Some(chrono::TimeDelta::minutes(n)),
+                "h" => Some(chrono::TimeDelta::hours(n)),
+                "d" => Some(chrono::TimeDelta::days(n)),
+                "w" => Some(chrono::TimeDelta::weeks(n)),
+                _ => None,
+            };
+            if let Some(delta) = delta {
+                let now = chrono::Utc::now().with_timezone(&api::blackboard::shanghai());
+                return Ok(Self::Instant(if future {
+                    now + delta
+                } else {
+                    now - delta
+                }));
+            }
+        }
+        if let Ok(d) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Self::Day(d));
+        }
+        if let Some(t) = Deadline::parse(s) {
+            return Ok(Self::Instant(t.time()));
+        }
+        anyhow::bail!("invalid time '{s}', expect e.g. 3d, +12h, 2w or 2025-03-01")
+    }
+}
+
+impl TimeBound {
+    fn day_start(d: chrono::NaiveDate) -> chrono::DateTime<chrono::FixedOffset> {
+        use chrono::TimeZone as _;
+        api::blackboard::shanghai()
+            .from_local_datetime(&d.and_time(chrono::NaiveTime::MIN))
+            .unwrap()
+    }
+
+    /// 作为起点时，日期取当天零点
+    pub fn start(&self) -> chrono::DateTime<chrono::FixedOffset> {
+        match self {
+            Self::Instant(t) => *t,
+            Self::Day(d) => Self::day_start(*d),
+        }
+    }
+
+    /// 作为终点时，日期包括当天
+    pub fn end(&self) -> chrono::DateTime<chrono::FixedOffset> {
+        match self {
+            Self::Instant(t) => *t,
+            Self::Day(d) => Self::day_start(*d) + chrono::TimeDelta::days(1),
+        }
+    }
+}
+
+/// 课程内容在 `root` 下的保存目录：按 [`CourseContent::path`] 逐级建立子目录
+fn content_dir(root: &std::path::Path, ct: &CourseContent) -> std::path::PathBuf {
+    let mut dir = root.to_owned();
+    dir.extend(ct.path().iter().map(|s| sanitize_filename(s)));
+    dir
+}
+
+/// 报告一个附件的下载结果：保存路径，或者因文件已存在而跳过
+fn report_download(ctx: &CommandCtx<'_>, name: &str, r: Option<&std::path::Path>) {
+    let msg = match r {
+        Some(path) => format!("{GR}+{GR:#} {name} -> {}", path.display()),
+        None => format!("{D}= {name} (已存在，跳过){D:#}"),
+    };
+    // 终端不可用时忽略
+    let _ = ctx.multi.println(msg);
+}
+
+/// 并发下载附件 `(名称, 链接)` 到目录 `dir`，同时最多下载 `jobs` 个.
+///
+/// 显示一个总进度条和每个文件的进度条. 单个附件下载失败不会中断其余附件，
+/// 失败的附件在全部结束后汇总报告.
+async fn download_attachments(
+    ctx: &CommandCtx<'_>,
+    client: &api::Client,
+    atts: &[&(String, String)],
+    dir: &std::path::Path,
+    on_conflict: OnConflict,
+    jobs: usize,
+) -> anyhow::Result<()> {
+    let tot = atts.len();
+    let pb = ctx.multi.add(pbar::new(tot as u64)).with_prefix("All");
+    pb.tick();
+
+    // `.part` 文件按附件名称区分，最终的保存路径在收到响应后通过 `claimed` 确定
+    let names = dedup_names(atts.iter().map(|(name, _)| name.as_str()));
+    let claimed = ClaimedPaths::default();
+    let results = futures_util::stream::iter(atts.iter().zip(&names))
+        .map(async |((name, uri), file_name)| {
+            let target = DownloadTarget::Dir {
+                dir,
+                name: file_name,
+                on_conflict,
+                claimed: Some(&claimed),
+            };
+            let fpb = ctx.multi.add(pbar::new_bytes(name));
+            let r = client
+                .course_attachment_download(uri, target, true, pbar::bytes_progress(&fpb))
+                .await;
+            fpb.finish_and_clear();
+            ctx.multi.remove(&fpb);
+            pb.inc(1);
+            if let Ok(path) = &r {
+                report_download(ctx, name, path.as_deref());
+            }
+            (name, r)
+        })
+        .buffer_unordered(jobs.max(1))
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    ctx.multi.remove(&pb);
+
+    let failures = results
+        .into_iter()
+        .filter_map(|(name, r)| r.err().map(|e| (name, e)))
+        .collect::<Vec<_>>();
+    for (name, e) in &failures {
+        eprintln!("{RD}下载失败{RD:#} {name}: {e:#}");
+    }
+    anyhow::ensure!(
+        failures.is_empty(),
+        "{} of {tot} attachments failed to download",
+        failures.len()
+    );
+    Ok(())
+}
+
 async fn build_client(enable_cache: bool) -> anyhow::Result<api::Client> {
     let mut builder =
         api::Client::builder().cookie_restore_path(Some(utils::default_user_agent_data_path()));
@@ -207,21 +397,40 @@ async fn load_blackboard(
     Ok((blackboard, sp))
 }
 
+/// 解析 `--course` 参数：配置文件中的课程别名、课程 ID 或课程标题的子串/正则表达式
+async fn course_selector(ctx: &CommandCtx<'_>, course: &str) -> anyhow::Result<CourseSelector> {
+    let cfg = config::read_cfg(&ctx.config_path)
+        .await
+        .context("read config file")?;
+    let course = cfg.resolve_course_alias(course);
+    log::debug!("filtering courses by {course}");
+    Ok(course.parse()?)
+}
+
 /// Blackboard, courses and spinner are returned. Spinner hasn't stopped.
+///
+/// 指定 `course` 时只保留匹配的课程 (见 [`course_selector`]).
 async fn load_client_courses(
     ctx: &CommandCtx<'_>,
     force: bool,
     only_current: bool,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<(Blackboard, Vec<CourseHandle>, AsyncSpinner)> {
     let (b, sp) = load_blackboard(ctx, !force, otp_code).await?;
 
     sp.set_message("fetching courses...");
-    let courses = b
+    let mut courses = b
         .get_courses(only_current)
         .await
         .context("fetch course handles")?;
 
+    if let Some(course) = course {
+        let sel = course_selector(ctx, course).await?;
+        courses.retain(|c| c.meta().matches(&sel));
+        anyhow::ensure!(!courses.is_empty(), "no course matches '{course}'");
+    }
+
     Ok((b, courses, sp))
 }
 
@@ -229,9 +438,10 @@ async fn load_courses(
     ctx: &CommandCtx<'_>,
     force: bool,
     only_current: bool,
+    course: Option<&str>,
     otp_code: String,
 ) -> anyhow::Result<Vec<CourseHandle>> {
-    let (_, r, _) = load_client_courses(ctx, force, only_current, otp_code).await?;
+    let (_, r, _) = load_client_courses(ctx, force, only_current, course, otp_code).await?;
     Ok(r)
 }
 
@@ -276,6 +486,9 @@ async fn command_init(ctx: &CommandCtx<'_>) -> anyhow::Result<()> {
         bark: None,
         secret_backend: config::SecretBackend::Plaintext,
         auto_supplement: None,
+        grades: None,
+        course_aliases: None,
+        mime_types: None,
     };
     config::write_cfg(&ctx.config_path, &cfg).await?;
 
@@ -353,10 +566,15 @@ pub async fn start(cli: Cli, m: &MultiProgress) -> anyhow::Result<()> {
                 }
             }
             Commands::Assignment(cmd) => cmd_assignment::run(cmd, &ctx).await?,
+            Commands::Quiz(cmd) => cmd_quiz::run(cmd, &ctx).await?,
             Commands::CourseContent(cmd) => cmd_course_content::run(cmd, &ctx).await?,
+            Commands::Course(cmd) => cmd_course::run(cmd, &ctx).await?,
+            Commands::Sync(cmd) => cmd_sync::run(cmd, &ctx).await?,
             Commands::CourseTable(cmd) => cmd_course_table::run(cmd, &ctx).await?,
             Commands::Announcement(cmd) => cmd_announcement::run(cmd, &ctx).await?,
             Commands::Video(cmd) => cmd_video::run(cmd, &ctx).await?,
+            Commands::Calendar(cmd) => cmd_calendar::run(cmd, &ctx).await?,
+            Commands::Forum(cmd) => cmd_forum::run(cmd, &ctx).await?,
             Commands::Grades(cmd) => cmd_grades::run(cmd, &ctx).await?,
             Commands::Syllabus(cmd) => cmd_syllabus::run(cmd, &ctx).await?,
 
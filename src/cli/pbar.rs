@@ -54,6 +54,30 @@ pub fn new_spinner_on(multi: &MultiProgress) -> AsyncSpinner {
     new_async_spinner(multi.add(ProgressBar::new_spinner()))
 }
 
+/// Create a progress bar for downloading a file, showing bytes and speed
+pub fn new_bytes(name: &str) -> ProgressBar {
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::with_template(
+            "{prefix} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}) ",
+        )
+        .unwrap()
+        .progress_chars("=> "),
+    );
+    pb.with_prefix(name.to_owned())
+}
+
+/// Progress callback `(downloaded, total)` updating a bar created by [`new_bytes`].
+/// The total is 0 when unknown.
+pub fn bytes_progress(pb: &ProgressBar) -> impl Fn(u64, u64) + '_ {
+    move |pos, total| {
+        if total > 0 {
+            pb.set_length(total);
+        }
+        pb.set_position(pos);
+    }
+}
+
 /// Create a new progress bar with a given length and a default style
 pub fn new(pb_len: u64) -> ProgressBar {
     let pb = ProgressBar::new(pb_len);
@@ -28,6 +28,15 @@ pub struct Config {
     pub secret_backend: SecretBackend,
 
     pub auto_supplement: Option<Vec<SupplementCourseConfig>>,
+
+    /// 成绩计算的自定义设置 (学分、权重)
+    pub grades: Option<Vec<GradeCourseConfig>>,
+
+    /// 课程别名 -> 课程 ID 或课程标题的子串/正则表达式，例如 `gs = "高等数学"`
+    pub course_aliases: Option<std::collections::BTreeMap<String, String>>,
+
+    /// 提交作业时按扩展名自定义的 MIME 类型，例如 `ipynb = "application/json"`
+    pub mime_types: Option<std::collections::BTreeMap<String, String>>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Clone)]
@@ -38,6 +47,25 @@ pub struct SupplementCourseConfig {
     pub class_id: String,
 }
 
+/// 单门课程的成绩计算设置，例如
+///
+/// ```toml
+/// [[grades]]
+/// course = "数学分析"
+/// credit = 5
+/// weights = { "作业" = 0.3, "期中" = 0.3, "期末" = 0.4 }
+/// ```
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct GradeCourseConfig {
+    /// 课程名称的子串
+    pub course: String,
+    /// 学分，计算平均绩点时使用，默认为 1
+    pub credit: Option<f64>,
+    /// 成绩项名称的子串 -> 权重. 设置后按权重计算总评，而不是使用教学网上的总计列
+    #[serde(default)]
+    pub weights: std::collections::BTreeMap<String, f64>,
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone)]
 pub struct TTShiTuConfig {
     #[serde(default)]
@@ -53,6 +81,14 @@ pub struct BarkConfig {
 }
 
 impl Config {
+    /// 将 `--course` 参数中的课程别名替换为其对应的值
+    pub fn resolve_course_alias<'a>(&'a self, course: &'a str) -> &'a str {
+        self.course_aliases
+            .as_ref()
+            .and_then(|m| m.get(course.trim()))
+            .map_or(course, String::as_str)
+    }
+
     pub fn redacted(&self) -> Self {
         let mut cfg = self.clone();
         cfg.redact_for_storage();
@@ -0,0 +1,470 @@
+//! 根据成绩中心的数据计算课程总评和绩点.
+//!
+//! 总评的来源按优先级依次为：配置文件中的自定义权重、教学网上的计算列 (总计/加权总计)、
+//! 所有已评分成绩项的分数之和. 绩点按北京大学的公式由百分制总评换算得到.
+//!
+//! 每次查询成绩后会将结果保存到本地的成绩历史中，用于发现新发布或被修改的成绩.
+
+use crate::{
+    api::blackboard::{GradeRecord, GradeStatus},
+    config::GradeCourseConfig,
+    utils,
+};
+use anyhow::Context as _;
+use compio::{buf::buf_try, fs};
+
+const HISTORY_FILE: &str = "grades-history.json";
+
+/// 课程总评的来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum TotalSource {
+    /// 按配置文件中的权重计算
+    Weighted,
+    /// 教学网上的计算列
+    Calculated(String),
+    /// 已评分成绩项的分数之和
+    Sum,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CourseTotal {
+    pub score: f64,
+    pub possible: f64,
+    pub source: TotalSource,
+}
+
+impl CourseTotal {
+    /// 百分制成绩
+    pub fn percent(&self) -> f64 {
+        self.score / self.possible * 100.0
+    }
+
+    pub fn gpa(&self) -> f64 {
+        gpa(self.percent())
+    }
+}
+
+/// 北京大学的绩点换算公式：60 分以上为 `4 - 3 * (100 - x)^2 / 1600`，不及格为 0.
+pub fn gpa(percent: f64) -> f64 {
+    if percent < 60.0 {
+        return 0.0;
+    }
+    let x = percent.min(100.0);
+    4.0 - 3.0 * (100.0 - x).powi(2) / 1600.0
+}
+
+/// 按学分加权的平均绩点，`items` 为 (总评, 学分)
+pub fn average_gpa<'a>(items: impl IntoIterator<Item = (&'a CourseTotal, f64)>) -> Option<f64> {
+    let (sum, credits) = items
+        .into_iter()
+        .filter(|(_, credit)| *credit > 0.0)
+        .fold((0.0, 0.0), |(sum, credits), (t, credit)| {
+            (sum + t.gpa() * credit, credits + credit)
+        });
+    (credits > 0.0).then(|| sum / credits)
+}
+
+/// 在配置中查找课程对应的设置 (课程名称包含 `course` 即视为匹配)
+pub fn find_config<'a>(
+    cfgs: &'a [GradeCourseConfig],
+    course_name: &str,
+) -> Option<&'a GradeCourseConfig> {
+    cfgs.iter().find(|c| course_name.contains(&c.course))
+}
+
+/// 计算一门课程当前的总评. 只统计已评分的成绩项，因此学期中得到的是目前为止的总评.
+pub fn course_total(
+    items: &[&GradeRecord],
+    cfg: Option<&GradeCourseConfig>,
+) -> Option<CourseTotal> {
+    let graded = |g: &&&GradeRecord| counted(g);
+
+    if let Some(cfg) = cfg.filter(|c| !c.weights.is_empty()) {
+        return weighted_total(items, cfg);
+    }
+
+    if let Some(col) = items
+        .iter()
+        .filter(|g| g.calculated && !g.column_name.contains("平时"))
+        .find(graded)
+    {
+        return Some(CourseTotal {
+            score: col.score.unwrap_or_default(),
+            possible: col.possible,
+            source: TotalSource::Calculated(col.column_name.clone()),
+        });
+    }
+
+    let (score, possible) = items
+        .iter()
+        .filter(|g| !g.calculated)
+        .filter(graded)
+        .fold((0.0, 0.0), |(s, p), g| {
+            (s + g.score.unwrap_or_default(), p + g.possible)
+        });
+    (possible > 0.0).then_some(CourseTotal {
+        score,
+        possible,
+        source: TotalSource::Sum,
+    })
+}
+
+/// 已评分且计入总评的成绩项
+fn counted(g: &GradeRecord) -> bool {
+    g.score.is_some() && g.possible > 0.0 && g.status != GradeStatus::Exempt
+}
+
+/// 每个成绩项归入名称匹配的第一个权重类别，类别内按分数之和计算得分率，
+/// 再按权重加权平均. 没有已评分成绩项的类别不参与计算.
+fn weighted_total(items: &[&GradeRecord], cfg: &GradeCourseConfig) -> Option<CourseTotal> {
+    let mut total = 0.0;
+    let mut weights = 0.0;
+    for (key, &weight) in &cfg.weights {
+        let (score, possible) = items
+            .iter()
+            .filter(|g| !g.calculated && counted(g))
+            .filter(|g| {
+                cfg.weights
+                    .keys()
+                    .find(|k| g.column_name.contains(k.as_str()))
+                    .is_some_and(|k| k == key)
+            })
+            .fold((0.0, 0.0), |(s, p), g| {
+                (s + g.score.unwrap_or_default(), p + g.possible)
+            });
+        if possible > 0.0 {
+            total += weight * score / possible;
+            weights += weight;
+        }
+    }
+    (weights > 0.0).then(|| CourseTotal {
+        score: total / weights * 100.0,
+        possible: 100.0,
+        source: TotalSource::Weighted,
+    })
+}
+
+/// 本地保存的成绩快照
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct GradeHistory {
+    entries: Vec<GradeEntry>,
+    /// 历次检测到的成绩变动
+    #[serde(default)]
+    changes: Vec<GradeChange>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct GradeEntry {
+    course_name: String,
+    column_name: String,
+    score: Option<f64>,
+    possible: f64,
+    #[serde(default)]
+    text: Option<String>,
+    /// 旧版本记录的快照中没有状态
+    #[serde(default)]
+    status: Option<GradeStatus>,
+    updated: chrono::DateTime<chrono::Local>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// 新发布的成绩
+    New,
+    /// 分数、等级或状态被修改
+    Modified,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GradeChange {
+    pub kind: ChangeKind,
+    pub course_name: String,
+    pub column_name: String,
+    pub score: Option<f64>,
+    pub possible: f64,
+    /// 非数值成绩的显示文本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub status: GradeStatus,
+    /// 修改前的分数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_score: Option<f64>,
+    /// 修改前的显示文本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_text: Option<String>,
+    /// 修改前的状态
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_status: Option<GradeStatus>,
+    pub time: chrono::DateTime<chrono::Local>,
+}
+
+/// 是否已有成绩 (分数、等级等) 或批改状态
+fn has_grade(score: Option<f64>, text: Option<&str>, status: GradeStatus) -> bool {
+    score.is_some() || text.is_some() || status != GradeStatus::NotGraded
+}
+
+impl GradeHistory {
+    pub async fn load() -> anyhow::Result<Self> {
+        let path = utils::data_dir().join(HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let buf = fs::read(&path).await?;
+        serde_json::from_slice(&buf).with_context(|| format!("parse {}", path.display()))
+    }
+
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let dir = utils::data_dir();
+        fs::create_dir_all(&dir).await?;
+        let buf = serde_json::to_vec_pretty(self)?;
+        buf_try!(@try fs::write(dir.join(HISTORY_FILE), buf).await);
+        Ok(())
+    }
+
+    /// 用本次查询到的成绩更新快照，返回新发布或被修改的成绩.
+    /// 第一次记录某门课程时只建立快照，不报告变动；获取失败的成绩项保留原有记录.
+    pub fn update(
+        &mut self,
+        records: &[GradeRecord],
+        now: chrono::DateTime<chrono::Local>,
+    ) -> Vec<GradeChange> {
+        let known = self
+            .entries
+            .iter()
+            .map(|e| e.course_name.clone())
+            .collect::<std::collections::HashSet<_>>();
+        let mut changes = Vec::new();
+        for r in records.iter().filter(|r| r.fetched) {
+            let entry = self
+                .entries
+                .iter_mut()
+                .find(|e| e.course_name == r.course_name && e.column_name == r.column_name);
+            let old = entry
+                .as_ref()
+                .map(|e| (e.score, e.text.clone(), e.status.unwrap_or_default()))
+                .filter(|(score, text, status)| has_grade(*score, text.as_deref(), *status));
+            match entry {
+                // 旧版本的记录没有文本和状态，只比较分数并补全
+                Some(e)
+                    if e.score == r.score
+                        && e.possible == r.possible
+                        && e.status.is_none_or(|s| s == r.status && e.text == r.text) =>
+                {
+                    e.text = r.text.clone();
+                    e.status = Some(r.status);
+                    continue;
+                }
+                Some(e) => {
+                    e.score = r.score;
+                    e.possible = r.possible;
+                    e.text = r.text.clone();
+                    e.status = Some(r.status);
+                    e.updated = now;
+                }
+                None => self.entries.push(GradeEntry {
+                    course_name: r.course_name.clone(),
+                    column_name: r.column_name.clone(),
+                    score: r.score,
+                    possible: r.possible,
+                    text: r.text.clone(),
+                    status: Some(r.status),
+                    updated: now,
+                }),
+            }
+            if !has_grade(r.score, r.text.as_deref(), r.status) || !known.contains(&r.course_name) {
+                continue;
+            }
+            let (old_score, old_text, old_status) = match old {
+                Some((score, text, status)) => (score, text, Some(status)),
+                None => (None, None, None),
+            };
+            changes.push(GradeChange {
+                kind: if old_status.is_some() {
+                    ChangeKind::Modified
+                } else {
+                    ChangeKind::New
+                },
+                course_name: r.course_name.clone(),
+                column_name: r.column_name.clone(),
+                score: r.score,
+                possible: r.possible,
+                text: r.text.clone(),
+                status: r.status,
+                old_score,
+                old_text,
+                old_status,
+                time: now,
+            });
+        }
+        self.changes.extend(changes.iter().cloned());
+        changes
+    }
+
+    /// 历次查询中在 `since` 之后检测到的成绩变动
+    pub fn changes_since(
+        &self,
+        since: chrono::DateTime<chrono::FixedOffset>,
+    ) -> impl Iterator<Item = &GradeChange> {
+        self.changes.iter().filter(move |c| c.time > since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, score: Option<f64>, possible: f64, calculated: bool) -> GradeRecord {
+        GradeRecord {
+            course_name: "数学分析 (2024-2025学年第2学期)".into(),
+            column_name: name.into(),
+            score,
+            possible,
+            calculated,
+            visible: true,
+            fetched: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_gpa() {
+        assert_eq!(gpa(100.0), 4.0);
+        assert_eq!(gpa(60.0), 1.0);
+        assert_eq!(gpa(59.9), 0.0);
+        assert!((gpa(90.0) - 3.8125).abs() < 1e-9);
+
+        let a = CourseTotal {
+            score: 100.0,
+            possible: 100.0,
+            source: TotalSource::Sum,
+        };
+        let b = CourseTotal {
+            score: 60.0,
+            possible: 100.0,
+            source: TotalSource::Sum,
+        };
+        assert_eq!(average_gpa([(&a, 3.0), (&b, 1.0)]), Some(3.25));
+        assert_eq!(average_gpa([(&a, 0.0)]), None);
+    }
+
+    #[test]
+    fn test_course_total() {
+        let recs = [
+            record("作业1", Some(8.0), 10.0, false),
+            record("作业2", None, 10.0, false),
+            record("期中考试", Some(72.0), 100.0, false),
+            record("平时成绩总计", Some(8.0), 10.0, true),
+        ];
+        let items = recs.iter().collect::<Vec<_>>();
+
+        let t = course_total(&items, None).unwrap();
+        assert_eq!(t.source, TotalSource::Sum);
+        assert_eq!((t.score, t.possible), (80.0, 110.0));
+
+        let mut with_total = items.clone();
+        let total = record("加权总计", Some(75.5), 100.0, true);
+        with_total.push(&total);
+        let t = course_total(&with_total, None).unwrap();
+        assert_eq!(t.source, TotalSource::Calculated("加权总计".into()));
+        assert_eq!(t.percent(), 75.5);
+
+        let cfg = GradeCourseConfig {
+            course: "数学分析".into(),
+            credit: Some(5.0),
+            weights: [
+                ("作业".into(), 0.3),
+                ("期中".into(), 0.3),
+                ("期末".into(), 0.4),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        // 期末尚未评分，只按作业和期中的权重计算
+        let t = course_total(&with_total, Some(&cfg)).unwrap();
+        assert_eq!(t.source, TotalSource::Weighted);
+        assert!((t.percent() - 76.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_history_update() {
+        let now = chrono::Local::now();
+        let mut h = GradeHistory::default();
+        let mut recs = vec![
+            record("作业1", Some(8.0), 10.0, false),
+            record("期中考试", None, 100.0, false),
+        ];
+        // 第一次只建立快照
+        assert!(h.update(&recs, now).is_empty());
+        assert!(h.update(&recs, now).is_empty());
+
+        recs[0].score = Some(9.0);
+        recs[1].score = Some(87.0);
+        recs.push(record("作业2", Some(10.0), 10.0, false));
+        let changes = h.update(&recs, now);
+        let summary = changes
+            .iter()
+            .map(|c| (c.kind, c.column_name.as_str(), c.score, c.old_score))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (ChangeKind::Modified, "作业1", Some(9.0), Some(8.0)),
+                (ChangeKind::New, "期中考试", Some(87.0), None),
+                (ChangeKind::New, "作业2", Some(10.0), None),
+            ]
+        );
+        assert_eq!(h.changes.len(), 3);
+        assert!(h.update(&recs, now).is_empty());
+        assert_eq!(h.changes_since(now.fixed_offset()).count(), 0);
+        let before = now - chrono::TimeDelta::seconds(1);
+        assert_eq!(h.changes_since(before.fixed_offset()).count(), 3);
+    }
+
+    #[test]
+    fn test_history_update_text_status() {
+        let now = chrono::Local::now();
+        let mut h = GradeHistory::default();
+        let mut recs = vec![record("论文", None, 0.0, false)];
+        h.update(&recs, now);
+
+        recs[0].status = GradeStatus::NeedsGrading;
+        let changes = h.update(&recs, now);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            (changes[0].kind, changes[0].status),
+            (ChangeKind::New, GradeStatus::NeedsGrading)
+        );
+
+        // 待批改变为等级成绩
+        recs[0].status = GradeStatus::Graded;
+        recs[0].text = Some("A-".into());
+        let changes = h.update(&recs, now);
+        assert_eq!(changes.len(), 1);
+        let c = &changes[0];
+        assert_eq!(c.kind, ChangeKind::Modified);
+        assert_eq!(c.text.as_deref(), Some("A-"));
+        assert_eq!(c.old_status, Some(GradeStatus::NeedsGrading));
+        assert!(h.update(&recs, now).is_empty());
+    }
+
+    #[test]
+    fn test_history_update_partial() {
+        let now = chrono::Local::now();
+        let mut h = GradeHistory::default();
+        let recs = vec![record("作业1", Some(8.0), 10.0, false)];
+        h.update(&recs, now);
+
+        // 获取失败的成绩项不覆盖原有记录
+        let mut failed = record("作业1", None, 10.0, false);
+        failed.fetched = false;
+        assert!(h.update(&[failed], now).is_empty());
+        assert!(h.update(&recs, now).is_empty());
+
+        // 第一次出现的课程只建立快照
+        let mut other = record("期中考试", Some(90.0), 100.0, false);
+        other.course_name = "线性代数 (2024-2025学年第2学期)".into();
+        assert!(h.update(&[other], now).is_empty());
+    }
+}
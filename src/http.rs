@@ -37,6 +37,14 @@ impl Client {
         })
     }
 
+    /// Start building a HEAD request.
+    pub fn head<U: IntoUrl>(&self, url: U) -> cyper::Result<RequestBuilder> {
+        Ok(RequestBuilder {
+            builder: self.http_client.head(url)?,
+            cookie_store: self.cookie_store.clone(),
+        })
+    }
+
     /// Start building a POST request.
     pub fn post<U: IntoUrl>(&self, url: U) -> cyper::Result<RequestBuilder> {
         Ok(RequestBuilder {
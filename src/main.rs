@@ -3,7 +3,10 @@ extern crate directories as dirs;
 mod api;
 mod cli;
 mod config;
+mod grade;
 mod http;
+mod markdown;
+mod mime;
 mod multipart;
 #[cfg(feature = "pdf")]
 mod pdf;
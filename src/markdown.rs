@@ -0,0 +1,382 @@
+//! 将教学网上的富文本 (HTML) 转换为 Markdown，并在终端中渲染 Markdown.
+//!
+//! 只处理教学网编辑器常见的标签：段落、标题、粗体/斜体、链接、图片、列表、表格、
+//! 代码以及 MathML 公式. 其余标签只保留其中的文字. 文字中的 Markdown 标记字符会被转义，
+//! 相对链接补全为教学网上的绝对地址.
+
+use scraper::{ElementRef, node::Node};
+
+/// 需要转义的 Markdown 标记字符
+const ESCAPED: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '#', '~'];
+
+/// 将页面中的相对链接补全为教学网上的绝对地址，页内锚点保持不变
+fn resolve_url(href: &str) -> String {
+    if href.starts_with('#') {
+        return href.to_owned();
+    }
+    url::Url::parse(crate::api::blackboard::API_BASE)
+        .and_then(|base| base.join(href))
+        .map_or_else(|_| href.to_owned(), String::from)
+}
+
+/// 将一个元素 (包括其自身) 转换为 Markdown
+pub fn from_element(el: ElementRef<'_>) -> String {
+    let mut w = Writer::default();
+    w.element(el);
+    w.finish()
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+}
+
+impl Writer {
+    fn finish(self) -> String {
+        let mut s = String::new();
+        let mut newlines = 0;
+        for c in self.out.trim().chars() {
+            if c == '\n' {
+                newlines += 1;
+                if newlines > 2 {
+                    continue;
+                }
+            } else {
+                newlines = 0;
+            }
+            s.push(c);
+        }
+        s
+    }
+
+    fn render_children(el: ElementRef<'_>) -> String {
+        let mut w = Writer::default();
+        w.children(el);
+        w.finish()
+    }
+
+    fn trim_line_end(&mut self) {
+        while self.out.ends_with(' ') {
+            self.out.pop();
+        }
+    }
+
+    fn block(&mut self) {
+        self.trim_line_end();
+        if self.out.is_empty() {
+            return;
+        }
+        while !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn line_break(&mut self) {
+        self.trim_line_end();
+        self.out.push('\n');
+    }
+
+    fn text(&mut self, t: &str) {
+        for c in t.chars() {
+            if c.is_whitespace() {
+                if !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+                    self.out.push(' ');
+                }
+            } else {
+                if ESCAPED.contains(&c) {
+                    self.out.push('\\');
+                }
+                self.out.push(c);
+            }
+        }
+    }
+
+    /// 行内元素: 给非空内容加上标记, 如 `**bold**`
+    fn wrap(&mut self, el: ElementRef<'_>, mark: &str) {
+        let inner = Self::render_children(el);
+        if inner.is_empty() {
+            return;
+        }
+        self.out.push_str(mark);
+        self.out.push_str(&inner.replace('\n', " "));
+        self.out.push_str(mark);
+    }
+
+    fn children(&mut self, el: ElementRef<'_>) {
+        for node in el.children() {
+            match node.value() {
+                Node::Text(t) => self.text(t),
+                Node::Element(_) => self.element(ElementRef::wrap(node).unwrap()),
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, el: ElementRef<'_>) {
+        let name = el.value().name();
+        match name {
+            "script" | "style" | "head" | "title" => {}
+            "br" => self.line_break(),
+            "hr" => {
+                self.block();
+                self.out.push_str("---");
+                self.block();
+            }
+            "p" | "div" | "section" | "article" | "blockquote" | "center" => {
+                self.block();
+                if name == "blockquote" {
+                    let inner = Self::render_children(el);
+                    for line in inner.lines() {
+                        self.out.push_str("> ");
+                        self.out.push_str(line);
+                        self.out.push('\n');
+                    }
+                } else {
+                    self.children(el);
+                }
+                self.block();
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let inner = Self::render_children(el);
+                if !inner.is_empty() {
+                    self.out.push_str(&"#".repeat(level));
+                    self.out.push(' ');
+                    self.out.push_str(&inner.replace('\n', " "));
+                }
+                self.block();
+            }
+            "strong" | "b" => self.wrap(el, "**"),
+            "em" | "i" => self.wrap(el, "*"),
+            "del" | "s" | "strike" => self.wrap(el, "~~"),
+            "code" => {
+                // 代码中的字符不转义
+                let code = el.text().collect::<String>();
+                let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !code.is_empty() {
+                    self.out.push_str(&format!("`{code}`"));
+                }
+            }
+            "pre" => {
+                self.block();
+                self.out.push_str("```\n");
+                self.out.push_str(el.text().collect::<String>().trim_end());
+                self.out.push_str("\n```");
+                self.block();
+            }
+            "a" => {
+                let inner = Self::render_children(el).replace('\n', " ");
+                match el.value().attr("href").map(str::trim) {
+                    Some(href) if !href.is_empty() && !href.starts_with("javascript:") => {
+                        let url = resolve_url(href);
+                        let text = el.text().collect::<String>();
+                        if inner.is_empty() || text.trim() == href {
+                            self.out.push_str(&format!("<{url}>"));
+                        } else {
+                            self.out.push_str(&format!("[{inner}]({url})"));
+                        }
+                    }
+                    _ => self.out.push_str(&inner),
+                }
+            }
+            "img" => {
+                let Some(src) = el.value().attr("src") else {
+                    return;
+                };
+                let alt = el.value().attr("alt").unwrap_or_default().trim();
+                let alt = alt.replace('[', "\\[").replace(']', "\\]");
+                self.out
+                    .push_str(&format!("![{alt}]({})", resolve_url(src.trim())));
+            }
+            "math" => {
+                // 优先使用 LaTeX 标注, 否则退化为纯文本
+                let tex = el
+                    .select(&scraper::Selector::parse("annotation").unwrap())
+                    .find(|a| {
+                        a.value()
+                            .attr("encoding")
+                            .is_some_and(|e| e.contains("tex"))
+                    })
+                    .map(|a| a.text().collect::<String>())
+                    .or_else(|| el.value().attr("alttext").map(ToOwned::to_owned))
+                    .unwrap_or_else(|| el.text().collect::<String>());
+                self.out.push_str(&format!("${}$", tex.trim()));
+            }
+            "ul" | "ol" => {
+                self.block();
+                let ordered = name == "ol";
+                let items = el
+                    .child_elements()
+                    .filter(|li| li.value().name() == "li")
+                    .enumerate();
+                for (i, li) in items {
+                    let marker = if ordered {
+                        format!("{}. ", i + 1)
+                    } else {
+                        "- ".to_owned()
+                    };
+                    let indent = " ".repeat(marker.len());
+                    let inner = Self::render_children(li);
+                    for (j, line) in inner.lines().enumerate() {
+                        if j == 0 {
+                            self.out.push_str(&marker);
+                        } else if !line.is_empty() {
+                            self.out.push_str(&indent);
+                        }
+                        self.out.push_str(line);
+                        self.out.push('\n');
+                    }
+                }
+                self.block();
+            }
+            "table" => {
+                self.block();
+                self.table(el);
+                self.block();
+            }
+            _ => self.children(el),
+        }
+    }
+
+    fn table(&mut self, el: ElementRef<'_>) {
+        let tr_sel = scraper::Selector::parse("tr").unwrap();
+        let rows = el
+            .select(&tr_sel)
+            .map(|tr| {
+                tr.child_elements()
+                    .filter(|c| matches!(c.value().name(), "td" | "th"))
+                    .map(|c| {
+                        Self::render_children(c)
+                            .replace('|', "\\|")
+                            .replace("\n\n", "<br>")
+                            .replace('\n', "<br>")
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<_>>();
+        let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for (i, row) in rows.iter().enumerate() {
+            self.out.push('|');
+            for j in 0..cols {
+                self.out.push(' ');
+                self.out.push_str(row.get(j).map_or("", String::as_str));
+                self.out.push_str(" |");
+            }
+            self.out.push('\n');
+            if i == 0 {
+                self.out.push('|');
+                self.out.push_str(&" --- |".repeat(cols));
+                self.out.push('\n');
+            }
+        }
+    }
+}
+
+/// 在终端中渲染 Markdown：标题加粗、链接和图片显示地址
+pub fn render_terminal(md: &str) -> String {
+    use crate::utils::style::*;
+    use std::sync::LazyLock;
+
+    static RE_IMG: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"!\[([^\]]*)\]\(([^)\s]+)\)").unwrap());
+    static RE_LINK: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap());
+    static RE_AUTOLINK: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"<(https?://[^>\s]+|/[^>\s]+)>").unwrap());
+    static RE_BOLD: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"\*\*([^*]+)\*\*").unwrap());
+    static RE_ESCAPED: LazyLock<regex::Regex> =
+        LazyLock::new(|| regex::Regex::new(r"\\([\\`*_\[\]<#~|])").unwrap());
+
+    let mut out = Vec::new();
+    for line in md.lines() {
+        if let Some(title) = line
+            .strip_prefix('#')
+            .map(|s| s.trim_start_matches('#'))
+            .and_then(|s| s.strip_prefix(' '))
+        {
+            out.push(format!("{H1}{title}{H1:#}"));
+            continue;
+        }
+        let line = RE_IMG.replace_all(line, format!("{D}[图片: $1]{D:#} {UL}$2{UL:#}"));
+        let line = RE_LINK.replace_all(&line, format!("$1 {D}({D:#}{UL}$2{UL:#}{D}){D:#}"));
+        let line = RE_AUTOLINK.replace_all(&line, format!("{UL}$1{UL:#}"));
+        let line = RE_BOLD.replace_all(&line, format!("{B}$1{B:#}"));
+        let line = RE_ESCAPED.replace_all(&line, "$1");
+        out.push(line.into_owned());
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_html(html: &str) -> String {
+        from_element(scraper::Html::parse_fragment(html).root_element())
+    }
+
+    #[test]
+    fn test_from_html_inline() {
+        let md = from_html(
+            r#"<p>请阅读 <a href="/bbcswebdav/xid-1_1">讲义</a>，<strong>周五</strong>前提交。</p>"#,
+        );
+        assert_eq!(
+            md,
+            "请阅读 [讲义](https://course.pku.edu.cn/bbcswebdav/xid-1_1)，**周五**前提交。"
+        );
+    }
+
+    #[test]
+    fn test_from_html_links() {
+        let md = from_html(
+            r##"<p><a href="/webapps/blackboard/content/listContent.jsp?course_id=_1_1">目录</a>
+                <a href="https://example.com/a">https://example.com/a</a>
+                <a href="#top">顶部</a>
+                <img src="/bbcswebdav/xid-2_1" alt="图[1]"></p>"##,
+        );
+        assert_eq!(
+            md,
+            "[目录](https://course.pku.edu.cn/webapps/blackboard/content/listContent.jsp?course_id=_1_1) \
+             <https://example.com/a> [顶部](#top) \
+             ![图\\[1\\]](https://course.pku.edu.cn/bbcswebdav/xid-2_1)"
+        );
+    }
+
+    #[test]
+    fn test_from_html_escape() {
+        let md = from_html("<p># 注意: a*b_c [1] <code>x*y</code></p><p>*斜体*</p>");
+        assert_eq!(md, "\\# 注意: a\\*b\\_c \\[1\\] `x*y`\n\n\\*斜体\\*");
+        assert_eq!(render_terminal(&md), "# 注意: a*b_c [1] `x*y`\n\n*斜体*");
+    }
+
+    #[test]
+    fn test_from_html_blocks() {
+        let md = from_html(
+            "<h3>要求</h3><p>第一段<br>第二行</p><ul><li>A</li><li>B<ol><li>b1</li></ol></li></ul>\
+             <p><img src=\"/img.png\" alt=\"示意图\"></p>",
+        );
+        assert_eq!(
+            md,
+            "### 要求\n\n第一段\n第二行\n\n- A\n- B\n\n  1. b1\n\n![示意图](https://course.pku.edu.cn/img.png)"
+        );
+    }
+
+    #[test]
+    fn test_from_html_table() {
+        let md = from_html(
+            "<table><tr><th>题号</th><th>分值</th></tr><tr><td>1</td><td>10</td></tr></table>",
+        );
+        assert_eq!(md, "| 题号 | 分值 |\n| --- | --- |\n| 1 | 10 |");
+    }
+
+    #[test]
+    fn test_from_html_math() {
+        let md = from_html(
+            r#"<p>求 <math><semantics><mi>x</mi><annotation encoding="application/x-tex">x^2</annotation></semantics></math> 的值</p>"#,
+        );
+        assert_eq!(md, "求 $x^2$ 的值");
+    }
+}
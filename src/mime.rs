@@ -0,0 +1,292 @@
+//! 上传文件时使用的 MIME 类型.
+//!
+//! 依次使用：配置文件中按扩展名自定义的类型、文件头 (magic bytes)、扩展名 (不区分大小写)，
+//! 最后对看起来是文本的文件使用 `text/plain`. docx、jar 等基于 zip 或 OLE 的格式无法仅凭
+//! 文件头区分，此时以扩展名为准.
+
+use compio::io::AsyncReadAt as _;
+use std::collections::BTreeMap;
+
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// 识别文件头需要读取的字节数 (tar 的特征位于偏移 257 处)
+const HEAD_LEN: usize = 512;
+
+/// 扩展名 (小写) 及其 MIME 类型
+const EXTENSIONS: &[(&str, &str)] = &[
+    // 文本与代码
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("txt", "text/plain"),
+    ("log", "text/plain"),
+    ("csv", "text/csv"),
+    ("tsv", "text/tab-separated-values"),
+    ("md", "text/markdown"),
+    ("markdown", "text/markdown"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("ts", "text/x-typescript"),
+    ("json", "application/json"),
+    ("ipynb", "application/x-ipynb+json"),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("toml", "application/toml"),
+    ("tex", "application/x-tex"),
+    ("bib", "application/x-bibtex"),
+    ("py", "text/x-python"),
+    ("c", "text/x-c"),
+    ("h", "text/x-c"),
+    ("cpp", "text/x-c++"),
+    ("cc", "text/x-c++"),
+    ("cxx", "text/x-c++"),
+    ("hpp", "text/x-c++"),
+    ("java", "text/x-java"),
+    ("rs", "text/x-rust"),
+    ("go", "text/x-go"),
+    ("m", "text/x-matlab"),
+    ("r", "text/x-r"),
+    ("sh", "application/x-sh"),
+    ("sql", "application/sql"),
+    // 图片
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("bmp", "image/bmp"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("heic", "image/heic"),
+    // 音视频
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("flac", "audio/flac"),
+    ("ogg", "audio/ogg"),
+    ("m4a", "audio/mp4"),
+    ("mp4", "video/mp4"),
+    ("mov", "video/quicktime"),
+    ("avi", "video/x-msvideo"),
+    ("mkv", "video/x-matroska"),
+    ("webm", "video/webm"),
+    // 文档
+    ("pdf", "application/pdf"),
+    ("rtf", "application/rtf"),
+    ("ps", "application/postscript"),
+    ("epub", "application/epub+zip"),
+    ("doc", "application/msword"),
+    ("xls", "application/vnd.ms-excel"),
+    ("ppt", "application/vnd.ms-powerpoint"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("ods", "application/vnd.oasis.opendocument.spreadsheet"),
+    ("odp", "application/vnd.oasis.opendocument.presentation"),
+    // 压缩包
+    ("zip", "application/zip"),
+    ("jar", "application/java-archive"),
+    ("tar", "application/x-tar"),
+    ("gz", "application/gzip"),
+    ("tgz", "application/gzip"),
+    ("bz2", "application/x-bzip2"),
+    ("xz", "application/x-xz"),
+    ("zst", "application/zstd"),
+    ("7z", "application/x-7z-compressed"),
+    ("rar", "application/vnd.rar"),
+    // 其他
+    ("exe", OCTET_STREAM),
+    ("bin", OCTET_STREAM),
+];
+
+/// 文件头特征 (各段的偏移和字节都须匹配) 及其 MIME 类型
+type Signature = (&'static [(usize, &'static [u8])], &'static str);
+
+const SIGNATURES: &[Signature] = &[
+    (&[(0, b"%PDF-")], "application/pdf"),
+    (&[(0, b"\x89PNG\r\n\x1a\n")], "image/png"),
+    (&[(0, b"\xff\xd8\xff")], "image/jpeg"),
+    (&[(0, b"GIF87a")], "image/gif"),
+    (&[(0, b"GIF89a")], "image/gif"),
+    (&[(0, b"RIFF"), (8, b"WEBP")], "image/webp"),
+    (&[(0, b"RIFF"), (8, b"WAVE")], "audio/wav"),
+    (&[(0, b"RIFF"), (8, b"AVI ")], "video/x-msvideo"),
+    (&[(0, b"II*\x00")], "image/tiff"),
+    (&[(0, b"MM\x00*")], "image/tiff"),
+    (&[(0, b"ID3")], "audio/mpeg"),
+    (&[(0, b"fLaC")], "audio/flac"),
+    (&[(0, b"OggS")], "audio/ogg"),
+    (&[(4, b"ftyp")], "video/mp4"),
+    (&[(0, b"\x1a\x45\xdf\xa3")], "video/x-matroska"),
+    (&[(0, b"{\\rtf")], "application/rtf"),
+    (&[(0, b"%!PS")], "application/postscript"),
+    (&[(0, b"PK\x03\x04")], "application/zip"),
+    (&[(0, b"PK\x05\x06")], "application/zip"),
+    (
+        &[(0, b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1")],
+        "application/x-ole-storage",
+    ),
+    (&[(0, b"Rar!\x1a\x07")], "application/vnd.rar"),
+    (&[(0, b"7z\xbc\xaf\x27\x1c")], "application/x-7z-compressed"),
+    (&[(0, b"\x1f\x8b")], "application/gzip"),
+    (&[(0, b"BZh")], "application/x-bzip2"),
+    (&[(0, b"\xfd7zXZ\x00")], "application/x-xz"),
+    (&[(0, b"\x28\xb5\x2f\xfd")], "application/zstd"),
+    (&[(257, b"ustar")], "application/x-tar"),
+];
+
+/// 文件头相同、只能通过扩展名区分的格式：文件头识别出的类型及可细分的扩展名
+const CONTAINERS: &[(&str, &[&str])] = &[
+    (
+        "application/zip",
+        &["docx", "xlsx", "pptx", "odt", "ods", "odp", "epub", "jar"],
+    ),
+    ("application/x-ole-storage", &["doc", "xls", "ppt"]),
+    ("video/mp4", &["m4a", "mov", "heic"]),
+    ("video/x-matroska", &["webm"]),
+    ("application/gzip", &["tgz"]),
+];
+
+/// 根据扩展名 (不区分大小写，可以带 `.`) 返回 MIME 类型
+pub fn from_extension(ext: &str) -> Option<&'static str> {
+    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, mime)| *mime)
+}
+
+/// 根据文件开头的若干字节找到匹配的文件头特征
+fn sniff(head: &[u8]) -> Option<&'static Signature> {
+    SIGNATURES.iter().find(|(parts, _)| {
+        parts
+            .iter()
+            .all(|(off, magic)| head.get(*off..*off + magic.len()) == Some(*magic))
+    })
+}
+
+/// 特征是否全部由可打印的 ASCII 字符组成，如 `ID3`、`BZh`，普通文本也可能以它们开头
+fn is_textual(sig: &Signature) -> bool {
+    sig.0
+        .iter()
+        .all(|(_, magic)| magic.iter().all(|b| b.is_ascii_graphic() || *b == b' '))
+}
+
+/// 是否像是文本文件：不含 NUL 的 UTF-8 (末尾可能截断了一个字符)
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.is_empty() || head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// 综合自定义类型 `overrides` (扩展名 -> MIME 类型)、文件头 `head` 和扩展名 `ext` 确定 MIME 类型
+pub fn guess(ext: &str, head: &[u8], overrides: &BTreeMap<String, String>) -> String {
+    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+    if let Some(mime) = overrides
+        .iter()
+        .find(|(e, _)| e.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+        .map(|(_, mime)| mime)
+    {
+        return mime.to_owned();
+    }
+
+    let by_ext = from_extension(&ext);
+    // 文本形式的特征只在扩展名未知或文件头不像文本时才优先于扩展名
+    if let Some((_, sniffed)) =
+        sniff(head).filter(|sig| by_ext.is_none() || !is_textual(sig) || !looks_like_text(head))
+    {
+        let sniffed = *sniffed;
+        let refined = CONTAINERS
+            .iter()
+            .any(|(m, exts)| *m == sniffed && exts.contains(&ext.as_str()));
+        return match by_ext {
+            Some(by_ext) if refined => by_ext,
+            // OLE 文件本身没有通用的 MIME 类型
+            _ if sniffed == "application/x-ole-storage" => OCTET_STREAM,
+            _ => sniffed,
+        }
+        .to_owned();
+    }
+
+    match by_ext {
+        Some(mime) => mime.to_owned(),
+        None if looks_like_text(head) => "text/plain".to_owned(),
+        None => OCTET_STREAM.to_owned(),
+    }
+}
+
+/// 读取文件开头并确定其 MIME 类型，见 [`guess`]
+pub async fn detect(
+    path: &std::path::Path,
+    overrides: &BTreeMap<String, String>,
+) -> std::io::Result<String> {
+    let f = compio::fs::File::open(path).await?;
+    let compio::BufResult(r, head) = f.read_at(Vec::with_capacity(HEAD_LEN), 0).await;
+    r?;
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    Ok(guess(&ext, &head, overrides))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_mime_type() {
+        assert_eq!(from_extension("html"), Some("text/html"));
+        assert_eq!(from_extension("png"), Some("image/png"));
+        assert_eq!(from_extension("mp3"), Some("audio/mpeg"));
+        assert_eq!(from_extension("unknown"), None);
+        assert_eq!(guess("unknown", b"", &BTreeMap::new()), OCTET_STREAM);
+    }
+
+    #[test]
+    fn test_guess() {
+        let none = BTreeMap::new();
+        // 扩展名不区分大小写
+        assert_eq!(guess("PDF", b"", &none), "application/pdf");
+        assert_eq!(guess("py", b"print(1)\n", &none), "text/x-python");
+        // 文件头优先于扩展名
+        assert_eq!(
+            guess("txt", b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n", &none),
+            "application/pdf"
+        );
+        assert_eq!(guess("mp3", b"ID3\x04\x00\x00", &none), "audio/mpeg");
+        // 以文本形式的特征开头的文本文件仍以扩展名为准
+        assert_eq!(guess("txt", b"BZh is a prefix\n", &none), "text/plain");
+        assert_eq!(guess("md", b"ID3 tags\n", &none), "text/markdown");
+        assert_eq!(guess("", b"BZh91AY&SY", &none), "application/x-bzip2");
+        assert_eq!(guess("", b"\x89PNG\r\n\x1a\n\0\0", &none), "image/png");
+        // zip 格式的 Office 文档以扩展名为准
+        let zip = b"PK\x03\x04\x14\x00\x06\x00";
+        assert_eq!(
+            guess("DOCX", zip, &none),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        );
+        assert_eq!(guess("pdf", zip, &none), "application/zip");
+        let ole = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1\0\0";
+        assert_eq!(guess("xls", ole, &none), "application/vnd.ms-excel");
+        assert_eq!(guess("dat", ole, &none), OCTET_STREAM);
+        // 未知扩展名的文本文件
+        assert_eq!(guess("cfg", "键 = 值\n".as_bytes(), &none), "text/plain");
+        assert_eq!(guess("dat", b"\x00\x01\x02", &none), OCTET_STREAM);
+
+        let overrides = BTreeMap::from([(".ipynb".to_owned(), "application/json".to_owned())]);
+        assert_eq!(guess("IPYNB", b"{}", &overrides), "application/json");
+    }
+}
@@ -36,10 +36,38 @@ pub fn cache_dir() -> std::path::PathBuf {
         .unwrap_or_else(|| projectdir().cache_dir().to_path_buf())
 }
 
+/// 用于保存需要长期保留的本地数据（如作业提交回执）的目录
+pub fn data_dir() -> std::path::PathBuf {
+    projectdir().data_dir().to_path_buf()
+}
+
 pub fn default_user_agent_data_path() -> std::path::PathBuf {
     cache_dir().join("ua.json")
 }
 
+/// [`with_cache`] 使用的缓存文件，由名称和结果的类型共同决定
+fn cache_path<T: 'static>(name: &str) -> std::path::PathBuf {
+    let name_hash = {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        name.hash(&mut hasher);
+        let type_id = std::any::TypeId::of::<T>();
+        type_id.hash(&mut hasher);
+        hasher.finish()
+    };
+    cache_dir().join(format!("with_cache-{name_hash:x}"))
+}
+
+/// 删除 [`with_cache`] 中名称为 `name`、类型为 `T` 的缓存，使下次调用重新获取
+pub async fn invalidate_cache<T: 'static>(name: &str) {
+    let path = cache_path::<T>(name);
+    if let Err(e) = fs::remove_file(&path).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        log::warn!("remove cache {}: {e}", path.display());
+    }
+}
+
 /// If the cache file exists and is not expired, return the deserialized content.
 /// Otherwise, execute the future, serialize the result to the cache file, and return the result.
 pub async fn with_cache<T, F>(
@@ -51,17 +79,21 @@ where
     F: std::future::Future<Output = anyhow::Result<T>>,
     T: serde::de::DeserializeOwned + serde::Serialize + 'static,
 {
-    let name_hash = {
-        use std::hash::{Hash, Hasher};
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        name.hash(&mut hasher);
-        let type_id = std::any::TypeId::of::<T>();
-        type_id.hash(&mut hasher);
-        hasher.finish()
-    };
-    let name = format!("with_cache-{name_hash:x}");
+    with_partial_cache(name, ttl, async { Ok((fut.await?, true)) }).await
+}
 
-    let path = &cache_dir().join(&name);
+/// 同 [`with_cache`]，但 `fut` 同时返回结果是否完整，不完整的结果 (如部分请求失败) 不写入缓存
+pub async fn with_partial_cache<T, F>(
+    name: &str,
+    ttl: Option<&std::time::Duration>,
+    fut: F,
+) -> anyhow::Result<T>
+where
+    F: std::future::Future<Output = anyhow::Result<(T, bool)>>,
+    T: serde::de::DeserializeOwned + serde::Serialize + 'static,
+{
+    let path = &cache_path::<T>(name);
+    let name = path.file_name().unwrap().to_string_lossy();
 
     if let Ok(f) = fs::File::open(path).await
         && let Some(ttl) = ttl
@@ -76,7 +108,11 @@ where
         }
     }
 
-    let r = fut.await?;
+    let (r, complete) = fut.await?;
+    if !complete {
+        log::debug!("partial result, not cached: {name}");
+        return Ok(r);
+    }
     fs::create_dir_all(path.parent().unwrap()).await?;
     let buf = serde_json::to_vec(&r)?;
     buf_try!(@try fs::write(path, buf).await);